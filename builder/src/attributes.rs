@@ -0,0 +1,363 @@
+//! Parsing of `#[builder(...)]` attributes.
+//!
+//! Every key is checked against the table of keys accepted on a container
+//! (the struct, enum or enum variant) or on a field. Unknown keys, values of
+//! the wrong shape and keys given twice are pushed onto `errors` instead of
+//! stopping at the first one, so the derive reports all of them at once.
+
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream, Parser};
+use syn::punctuated::Punctuated;
+use syn::{Attribute, Ident, LitChar, LitStr, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeInfo {
+    pub tag: Ident,
+    pub value: AttributeValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Flag,
+    Str(String),
+    Char(char),
+    List(Vec<AttributeInfo>),
+    Paths(Vec<syn::Path>),
+    Metas(Vec<syn::Meta>),
+}
+
+impl AttributeValue {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AttributeValue::Str(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+pub struct Key {
+    name: &'static str,
+    kind: Kind,
+    repeatable: bool,
+}
+
+pub enum Kind {
+    Flag,
+    Str,
+    Char,
+    FlagOrStr,
+    Choice(&'static [&'static str]),
+    List(&'static [Key]),
+    Paths,
+    Metas,
+}
+
+const fn key(name: &'static str, kind: Kind) -> Key {
+    Key { name, kind, repeatable: false }
+}
+
+const fn repeatable(name: &'static str, kind: Kind) -> Key {
+    Key { name, kind, repeatable: true }
+}
+
+const CONTAINER_SETTER_KEYS: &[Key] = &[
+    key("into", Kind::Flag),
+    key("prefix", Kind::Str),
+];
+
+const FIELD_SETTER_KEYS: &[Key] = &[
+    key("into", Kind::Flag),
+    key("name", Kind::Str),
+    // Accepted for compatibility, `Option` setters take the inner type anyway.
+    key("strip_option", Kind::Flag),
+    key("wrap", Kind::Flag),
+];
+
+const BUILD_FN_KEYS: &[Key] = &[
+    key("error", Kind::Str),
+    key("validate", Kind::Str),
+    key("name", Kind::Str),
+];
+
+pub const CONTAINER_KEYS: &[Key] = &[
+    key("typestate", Kind::Flag),
+    key("default", Kind::Flag),
+    key("setter", Kind::List(CONTAINER_SETTER_KEYS)),
+    key("try_setter", Kind::Flag),
+    key("pattern", Kind::Choice(&["mutable", "owned", "immutable"])),
+    key("build_fn", Kind::List(BUILD_FN_KEYS)),
+    key("env_prefix", Kind::Str),
+    key("no_std", Kind::Flag),
+    key("crate", Kind::Str),
+    key("name", Kind::Str),
+    key("vis", Kind::Str),
+    repeatable("derive", Kind::Paths),
+    repeatable("struct_attr", Kind::Metas),
+    key("cli", Kind::Flag),
+    key("const", Kind::Flag),
+    key("merge", Kind::Flag),
+];
+
+pub const FIELD_KEYS: &[Key] = &[
+    key("each", Kind::Str),
+    key("default", Kind::FlagOrStr),
+    key("setter", Kind::List(FIELD_SETTER_KEYS)),
+    key("try_setter", Kind::Flag),
+    key("name", Kind::Str),
+    repeatable("requires", Kind::Str),
+    repeatable("conflicts_with", Kind::Str),
+    repeatable("group", Kind::Str),
+    key("exactly_one", Kind::Flag),
+    key("at_least_one", Kind::Flag),
+    key("at_most_one", Kind::Flag),
+    key("merge", Kind::Choice(&["replace", "append", "keep_first"])),
+    key("env", Kind::Str),
+    key("sub_builder", Kind::Flag),
+    key("private", Kind::Flag),
+    key("skip", Kind::Flag),
+    repeatable("field_attr", Kind::Metas),
+    key("long", Kind::Str),
+    key("short", Kind::Char),
+    key("skip_cli", Kind::Flag),
+    key("optional", Kind::Flag),
+    key("required", Kind::Flag),
+];
+
+impl Key {
+    fn expected(&self, path: &str) -> String {
+        let usages = match self.kind {
+            Kind::Flag => vec![self.name.to_owned()],
+            Kind::Str => vec![format!("{} = \"...\"", self.name)],
+            Kind::Char => vec![format!("{} = '.'", self.name)],
+            Kind::FlagOrStr => vec![self.name.to_owned(), format!("{} = \"...\"", self.name)],
+            Kind::Choice(choices) => vec![format!("{} = \"{}\"", self.name, choices.join("\" | \""))],
+            Kind::List(_) | Kind::Paths | Kind::Metas => vec![format!("{}(...)", self.name)],
+        };
+
+        let usages = usages
+            .iter()
+            .map(|usage| format!("`{}({})`", path, usage))
+            .collect::<Vec<_>>()
+            .join(" or ");
+
+        format!("expected {}", usages)
+    }
+}
+
+struct Entry {
+    tag: Ident,
+    value: EntryValue,
+    tokens: TokenStream,
+}
+
+enum EntryValue {
+    Flag,
+    Str(LitStr),
+    Char(LitChar),
+    Other,
+    List(Group),
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let tag = input.call(Ident::parse_any)?;
+
+        let (value, tokens) = if input.peek(Token![=]) {
+            let eq: Token![=] = input.parse()?;
+            let lit: syn::Lit = input.parse()?;
+            let tokens = quote!(#tag #eq #lit);
+            match lit {
+                syn::Lit::Str(lit) => (EntryValue::Str(lit), tokens),
+                syn::Lit::Char(lit) => (EntryValue::Char(lit), tokens),
+                _ => (EntryValue::Other, tokens),
+            }
+        } else if input.peek(syn::token::Paren) {
+            let group = match input.parse()? {
+                TokenTree::Group(group) => group,
+                _ => unreachable!(),
+            };
+            let tokens = quote!(#tag #group);
+            (EntryValue::List(group), tokens)
+        } else {
+            (EntryValue::Flag, quote!(#tag))
+        };
+
+        Ok(Self { tag, value, tokens })
+    }
+}
+
+pub fn parse(attributes: &[Attribute], keys: &[Key], errors: &mut Vec<syn::Error>) -> Vec<AttributeInfo> {
+    let mut seen = Vec::new();
+    let mut parsed = Vec::new();
+
+    for attribute in attributes.iter().filter(|attribute| attribute.path.is_ident("builder")) {
+        let path = &attribute.path;
+        let tokens = &attribute.tokens;
+        let enclosing = quote!(#path #tokens);
+
+        match attribute.parse_args_with(Punctuated::<Entry, Token![,]>::parse_terminated) {
+            Ok(entries) => parsed.extend(check(entries, "builder", &enclosing, keys, &mut seen, errors)),
+            Err(error) => errors.push(error),
+        }
+    }
+
+    parsed
+}
+
+fn check(
+    entries: Punctuated<Entry, Token![,]>,
+    path: &str,
+    enclosing: &dyn ToTokens,
+    keys: &[Key],
+    seen: &mut Vec<String>,
+    errors: &mut Vec<syn::Error>,
+) -> Vec<AttributeInfo> {
+    let mut checked = Vec::new();
+
+    for entry in entries {
+        let name = entry.tag.unraw().to_string();
+
+        let key = match keys.iter().find(|key| key.name == name) {
+            Some(key) => key,
+            None => {
+                errors.push(unknown_key(&name, path, enclosing, keys));
+                continue;
+            },
+        };
+
+        if !key.repeatable && seen.contains(&name) {
+            errors.push(syn::Error::new_spanned(&entry.tag, format!("duplicate `{}` attribute", name)));
+            continue;
+        }
+        seen.push(name);
+
+        let value = match (&key.kind, entry.value) {
+            (Kind::Flag, EntryValue::Flag) | (Kind::FlagOrStr, EntryValue::Flag) => AttributeValue::Flag,
+            (Kind::Str, EntryValue::Str(lit)) | (Kind::FlagOrStr, EntryValue::Str(lit)) => {
+                AttributeValue::Str(lit.value())
+            },
+            (Kind::Char, EntryValue::Char(lit)) => AttributeValue::Char(lit.value()),
+            (Kind::Choice(choices), EntryValue::Str(lit)) if choices.contains(&lit.value().as_str()) => {
+                AttributeValue::Str(lit.value())
+            },
+            (Kind::List(nested_keys), EntryValue::List(group)) => {
+                let tag = &entry.tag;
+                let nested_enclosing = quote!(#tag #group);
+
+                match Punctuated::<Entry, Token![,]>::parse_terminated.parse2(group.stream()) {
+                    Ok(nested) => AttributeValue::List(check(
+                        nested,
+                        key.name,
+                        &nested_enclosing,
+                        nested_keys,
+                        &mut Vec::new(),
+                        errors,
+                    )),
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    },
+                }
+            },
+            (Kind::Paths, EntryValue::List(group)) => {
+                match Punctuated::<syn::Path, Token![,]>::parse_terminated.parse2(group.stream()) {
+                    Ok(paths) => AttributeValue::Paths(paths.into_iter().collect()),
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    },
+                }
+            },
+            (Kind::Metas, EntryValue::List(group)) => {
+                match Punctuated::<syn::Meta, Token![,]>::parse_terminated.parse2(group.stream()) {
+                    Ok(metas) => AttributeValue::Metas(metas.into_iter().collect()),
+                    Err(error) => {
+                        errors.push(error);
+                        continue;
+                    },
+                }
+            },
+            _ => {
+                errors.push(syn::Error::new_spanned(&entry.tokens, key.expected(path)));
+                continue;
+            },
+        };
+
+        checked.push(AttributeInfo { tag: entry.tag, value });
+    }
+
+    checked
+}
+
+fn unknown_key(name: &str, path: &str, enclosing: &dyn ToTokens, keys: &[Key]) -> syn::Error {
+    let closest = keys
+        .iter()
+        .map(|key| (edit_distance(name, key.name), key))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance);
+
+    let message = match closest {
+        Some((_, key)) => key.expected(path),
+        None => {
+            let names = keys
+                .iter()
+                .map(|key| format!("`{}`", key.name))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("unknown attribute `{}` in `{}(...)`, expected one of {}", name, path, names)
+        },
+    };
+
+    syn::Error::new_spanned(enclosing, message)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+pub fn find_attribute<'a>(attributes: &'a [AttributeInfo], path: &[&str]) -> Option<&'a AttributeValue> {
+    let (tag, rest) = path.split_first()?;
+
+    attributes
+        .iter()
+        .filter(|attr| attr.tag == tag)
+        .find_map(|attr| match (&attr.value, rest) {
+            (value, []) => Some(value),
+            (AttributeValue::List(nested), rest) => find_attribute(nested, rest),
+            _ => None,
+        })
+}
+
+pub fn find_metas<'a>(attributes: &'a [AttributeInfo], tag: &'a str) -> impl Iterator<Item = &'a syn::Meta> {
+    attributes
+        .iter()
+        .filter(move |attr| attr.tag == tag)
+        .flat_map(|attr| match &attr.value {
+            AttributeValue::Metas(metas) => metas.as_slice(),
+            _ => &[],
+        })
+}
+
+pub fn combine(errors: Vec<syn::Error>) -> syn::Result<()> {
+    match errors.into_iter().reduce(|mut combined, error| {
+        combined.combine(error);
+        combined
+    }) {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,301 @@
+//! `#[builder(cli)]`, which generates `parse_args` on the builder so it can be
+//! filled from command-line arguments.
+//!
+//! Every field becomes a `--long` flag, named after the field (or after the
+//! `each` setter for collections) with underscores turned into dashes, and
+//! optionally a `-s` short flag. Tuple struct fields have no name to use and
+//! need `long` or `name`. Values are given as `--flag value` or
+//! `--flag=value` and parsed with `FromStr`. `bool` fields are switches that
+//! take no value, `each` collections accept the flag repeatedly and maps take
+//! `KEY=VALUE` pairs. `--help` is answered with a generated usage text.
+//!
+//! Fields marked `skip_cli` get no flag, so their types need not implement
+//! `FromStr`; they are left for the builder's setters.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Ident, Member, Type};
+
+use crate::attributes::AttributeValue;
+use crate::{snake_case, Collection, FieldInfo, StructInfo};
+
+pub fn error_variants(struct_info: &StructInfo) -> (TokenStream, TokenStream) {
+    if !struct_info.has_flag("cli") {
+        return (TokenStream::new(), TokenStream::new());
+    }
+
+    let error_name = struct_info.error_ident();
+    let alloc = struct_info.container().alloc();
+
+    let variants = quote! {
+        InvalidArgument {
+            argument: #alloc::string::String,
+            error: #alloc::string::String,
+        },
+        HelpRequested(&'static str),
+    };
+    let display = quote! {
+        #error_name::InvalidArgument { argument, error } => {
+            ::core::write!(f, "invalid argument {}: {}", argument, error)
+        },
+        #error_name::HelpRequested(help) => ::core::write!(f, "{}", help),
+    };
+
+    (variants, display)
+}
+
+pub fn expand(struct_info: &StructInfo) -> syn::Result<TokenStream> {
+    if !struct_info.has_flag("cli") {
+        return Ok(TokenStream::new());
+    }
+
+    let struct_name = &struct_info.ident;
+    let builder_fn_name = struct_info.builder_fn_ident();
+    let error_name = struct_info.error_ident();
+    let container = struct_info.container();
+    let alloc = container.alloc();
+    let vis = container.vis();
+    let (_, ty_generics, _) = struct_info.generics.split_for_impl();
+
+    let flags = struct_info.fields
+        .iter()
+        .filter(|field| !field.is_skipped() && !field.is_sub_builder() && !field.has_flag(&["skip_cli"]))
+        .map(Flag::new)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    let help = help(struct_info, &flags);
+    let arms = flags.iter().map(|flag| flag.arm(&error_name, &alloc));
+    let bounds = flags.iter().flat_map(|flag| flag.value_types()).map(|ty| {
+        quote!(#ty: ::core::str::FromStr, <#ty as ::core::str::FromStr>::Err: ::core::fmt::Display)
+    });
+
+    Ok(quote! {
+        #vis fn parse_args<__I>(args: __I) -> ::core::result::Result<Self, #error_name>
+        where __I: ::core::iter::IntoIterator<Item = #alloc::string::String>, #(#bounds,)*
+        {
+            fn __next_value<__I>(
+                flag: &str,
+                inline: ::core::option::Option<#alloc::string::String>,
+                args: &mut __I,
+            ) -> ::core::result::Result<#alloc::string::String, #error_name>
+            where __I: ::core::iter::Iterator<Item = #alloc::string::String>
+            {
+                match inline {
+                    ::core::option::Option::Some(value) => ::core::result::Result::Ok(value),
+                    ::core::option::Option::None => ::core::iter::Iterator::next(args).ok_or_else(|| {
+                        #error_name::InvalidArgument {
+                            argument: #alloc::borrow::ToOwned::to_owned(flag),
+                            error: #alloc::string::String::from("missing value"),
+                        }
+                    }),
+                }
+            }
+
+            fn __parse<__T>(flag: &str, value: &str) -> ::core::result::Result<__T, #error_name>
+            where __T: ::core::str::FromStr, __T::Err: ::core::fmt::Display
+            {
+                <__T as ::core::str::FromStr>::from_str(value).map_err(|error| #error_name::InvalidArgument {
+                    argument: #alloc::borrow::ToOwned::to_owned(flag),
+                    error: #alloc::string::ToString::to_string(&error),
+                })
+            }
+
+            let mut __builder = <#struct_name #ty_generics>::#builder_fn_name();
+            let mut __args = ::core::iter::IntoIterator::into_iter(args);
+
+            while let ::core::option::Option::Some(__arg) = ::core::iter::Iterator::next(&mut __args) {
+                let (__flag, __inline) = match __arg.find('=') {
+                    ::core::option::Option::Some(index) if __arg.starts_with("--") => (
+                        #alloc::borrow::ToOwned::to_owned(&__arg[..index]),
+                        ::core::option::Option::Some(#alloc::borrow::ToOwned::to_owned(&__arg[index + 1..])),
+                    ),
+                    _ => (__arg, ::core::option::Option::None),
+                };
+
+                match __flag.as_str() {
+                    "--help" => return ::core::result::Result::Err(#error_name::HelpRequested(#help)),
+                    #(#arms)*
+                    _ => {
+                        return ::core::result::Result::Err(#error_name::InvalidArgument {
+                            argument: __flag,
+                            error: #alloc::string::String::from("unknown argument"),
+                        });
+                    },
+                }
+            }
+
+            ::core::result::Result::Ok(__builder)
+        }
+    })
+}
+
+enum Value<'a> {
+    Switch,
+    Single(&'a Type),
+    Sequence(Type),
+    Map(Type, Type),
+}
+
+struct Flag<'a> {
+    field: &'a FieldInfo,
+    long: String,
+    short: Option<char>,
+    value: Value<'a>,
+}
+
+impl<'a> Flag<'a> {
+    fn new(field: &'a FieldInfo) -> syn::Result<Self> {
+        let value = match field.collection() {
+            Some(Collection::Sequence(item)) => Value::Sequence(item),
+            Some(Collection::Map(key, value)) => Value::Map(key, value),
+            None if field.is_repeated() => {
+                return Err(syn::Error::new_spanned(
+                    &field.ident,
+                    "`cli` cannot parse the items of this collection, only standard collections are supported",
+                ));
+            },
+            None => match field.setter_type() {
+                Type::Path(path) if path.qself.is_none() && path.path.is_ident("bool") => Value::Switch,
+                ty => Value::Single(ty),
+            },
+        };
+
+        // Tuple fields are only known by position, which makes no flag name.
+        let long = match field.attribute(&["long"]).and_then(AttributeValue::as_str) {
+            Some(long) => long.to_owned(),
+            None if matches!(field.member, Member::Unnamed(_))
+                && field.attribute(&["name"]).is_none()
+                && !field.is_repeated() =>
+            {
+                return Err(syn::Error::new_spanned(
+                    &field.ty,
+                    "`cli` needs a flag name for tuple struct fields, add `long = \"...\"` or `name = \"...\"`",
+                ));
+            },
+            None => field
+                .each_name()
+                .as_ref()
+                .map_or_else(|| kebab_case(&field.ident), kebab_case),
+        };
+        let short = match field.attribute(&["short"]) {
+            Some(AttributeValue::Char(short)) => Some(*short),
+            _ => None,
+        };
+
+        Ok(Self { field, long, short, value })
+    }
+
+    fn value_types(&self) -> Vec<&Type> {
+        match &self.value {
+            Value::Switch => Vec::new(),
+            Value::Single(ty) => vec![ty],
+            Value::Sequence(item) => vec![item],
+            Value::Map(key, value) => vec![key, value],
+        }
+    }
+
+    fn usage(&self) -> String {
+        let value_name = self.long.to_uppercase().replace('-', "_");
+        let value = match self.value {
+            Value::Switch => String::new(),
+            Value::Single(_) => format!(" <{}>", value_name),
+            Value::Sequence(_) => format!(" <{}>...", value_name),
+            Value::Map(..) => " <KEY=VALUE>...".to_owned(),
+        };
+
+        match self.short {
+            Some(short) => format!("-{}, --{}{}", short, self.long, value),
+            None => format!("    --{}{}", self.long, value),
+        }
+    }
+
+    fn arm(&self, error_name: &Ident, alloc: &TokenStream) -> TokenStream {
+        let parameter_name = &self.field.ident;
+        let long = format!("--{}", self.long);
+        let short = self.short.map(|short| format!("-{}", short)).into_iter();
+        let next_value = quote!(__next_value(&__flag, __inline, &mut __args)?);
+
+        let update = match &self.value {
+            Value::Switch => {
+                let stored = self.field.stored_value(quote!(__value));
+                quote! {
+                    let __value = match __inline {
+                        ::core::option::Option::Some(value) => __parse::<bool>(&__flag, &value)?,
+                        ::core::option::Option::None => true,
+                    };
+                    __builder.#parameter_name = #stored;
+                }
+            },
+            Value::Single(ty) => {
+                let stored = self.field.stored_value(quote!(__value));
+                quote! {
+                    let __value = __parse::<#ty>(&__flag, &#next_value)?;
+                    __builder.#parameter_name = #stored;
+                }
+            },
+            Value::Sequence(item) => {
+                let collection = self.field.collection_mut();
+                quote! {
+                    let __value = __parse::<#item>(&__flag, &#next_value)?;
+                    ::core::iter::Extend::extend(#collection, ::core::iter::once(__value));
+                }
+            },
+            Value::Map(key, value) => {
+                let collection = self.field.collection_mut();
+                quote! {
+                    let __pair = #next_value;
+                    let (__key, __value) = match __pair.find('=') {
+                        ::core::option::Option::Some(index) => (&__pair[..index], &__pair[index + 1..]),
+                        ::core::option::Option::None => {
+                            return ::core::result::Result::Err(#error_name::InvalidArgument {
+                                argument: __flag,
+                                error: #alloc::string::String::from("expected KEY=VALUE"),
+                            });
+                        },
+                    };
+                    let __entry = (__parse::<#key>(&__flag, __key)?, __parse::<#value>(&__flag, __value)?);
+                    ::core::iter::Extend::extend(#collection, ::core::iter::once(__entry));
+                }
+            },
+        };
+
+        quote! {
+            #long #(| #short)* => {
+                #update
+            },
+        }
+    }
+}
+
+fn help(struct_info: &StructInfo, flags: &[Flag]) -> String {
+    let mut program = kebab_case(&struct_info.ident);
+    if let Some(variant) = &struct_info.variant {
+        program = format!("{}-{}", program, kebab_case(variant));
+    }
+
+    let mut rows: Vec<(String, &str)> = flags
+        .iter()
+        .map(|flag| {
+            let note = if flag.field.is_required() {
+                "(required)"
+            } else {
+                ""
+            };
+            (flag.usage(), note)
+        })
+        .collect();
+    rows.push(("    --help".to_owned(), "Print this help"));
+
+    let width = rows.iter().map(|(usage, _)| usage.len()).max().unwrap_or(0);
+    let options = rows
+        .iter()
+        .map(|(usage, note)| format!("  {:width$}  {}", usage, note, width = width).trim_end().to_owned())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    format!("Usage: {} [OPTIONS]\n\nOptions:\n{}\n", program, options)
+}
+
+fn kebab_case(ident: &Ident) -> String {
+    snake_case(ident).replace('_', "-")
+}
@@ -0,0 +1,137 @@
+//! `#[builder(const)]`, for builders used in constant expressions such as the
+//! initialiser of a `static`.
+//!
+//! The builder function and the setters become `const fn`, and the builder
+//! gets `build_const`, which panics on a missing field instead of returning
+//! an error. Inside a constant that panic is reported at compile time.
+//!
+//! Only what a `const fn` can do is allowed: the builder uses the owned
+//! pattern, setters store their argument as it is and defaults have to be
+//! constant expressions. Const setters cannot drop the value they replace, so
+//! field types must not need dropping, e.g. `&'static str` instead of
+//! `String`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::attributes::{self, AttributeInfo, AttributeValue, find_attribute};
+use crate::{data_from_fields, FieldInfo, StructInfo};
+
+pub fn expand(struct_info: &StructInfo) -> syn::Result<TokenStream> {
+    let const_tag = match struct_info.attributes.iter().find(|attr| attr.tag == "const") {
+        Some(attr) => &attr.tag,
+        None => return Ok(TokenStream::new()),
+    };
+
+    let mut errors = Vec::new();
+    unsupported(&struct_info.attributes, &["env_prefix", "try_setter", "default"], &mut errors);
+    if let Some(AttributeValue::Str(pattern)) = struct_info.attribute(&["pattern"]) {
+        if pattern != "owned" {
+            let tag = &struct_info.attributes.iter().find(|attr| attr.tag == "pattern").unwrap().tag;
+            errors.push(syn::Error::new_spanned(tag, "const builders always use the owned pattern"));
+        }
+    }
+    if crate::validate::has_checks(struct_info) {
+        errors.push(syn::Error::new_spanned(
+            const_tag,
+            "const builders cannot be combined with build_fn(validate), requires, conflicts_with or group",
+        ));
+    }
+
+    for field in &struct_info.fields {
+        unsupported(&field.attributes, &["each", "sub_builder", "env", "try_setter"], &mut errors);
+
+        let default = field.attributes.iter().find(|attr| attr.tag == "default");
+        let missing_default = match default {
+            Some(attr) if attr.value == AttributeValue::Flag => Some(&attr.tag),
+            None if field.is_skipped() => field.attributes.iter().find(|attr| attr.tag == "skip").map(|attr| &attr.tag),
+            _ => None,
+        };
+        if let Some(tag) = missing_default {
+            errors.push(syn::Error::new_spanned(
+                tag,
+                "const builders need a constant `default = \"...\"` expression",
+            ));
+        }
+    }
+
+    attributes::combine(errors)?;
+
+    let struct_name = &struct_info.ident;
+    let builder_name = struct_info.builder_ident();
+    let constructor = struct_info.constructor();
+    let vis = struct_info.container().vis();
+    let (_, ty_generics, _) = struct_info.generics.split_for_impl();
+
+    let parameter_names = struct_info.fields
+        .iter()
+        .filter(|field| !field.is_skipped())
+        .map(|field| &field.ident);
+    let field_builders = data_from_fields(&struct_info.fields, build_const_field);
+
+    Ok(quote! {
+        #vis const fn build_const(self) -> #struct_name #ty_generics {
+            let #builder_name { #(#parameter_names,)* __marker: _ } = self;
+
+            #constructor {
+                #(#field_builders)*
+            }
+        }
+    })
+}
+
+fn unsupported(attributes: &[AttributeInfo], names: &[&str], errors: &mut Vec<syn::Error>) {
+    let mut setter = |name: &str| {
+        if find_attribute(attributes, &["setter", name]).is_some() {
+            let tag = &attributes.iter().find(|attr| attr.tag == "setter").unwrap().tag;
+            errors.push(syn::Error::new_spanned(tag, format!("`setter({})` is not supported by const builders", name)));
+        }
+    };
+    setter("into");
+    setter("wrap");
+
+    for attr in attributes.iter().filter(|attr| names.iter().any(|name| attr.tag == name)) {
+        errors.push(syn::Error::new_spanned(
+            &attr.tag,
+            format!("`{}` is not supported by const builders", attr.tag),
+        ));
+    }
+}
+
+fn build_const_field(field: &FieldInfo) -> TokenStream {
+    let parameter_name = &field.ident;
+    let member = &field.member;
+    let default = field.default_value();
+
+    if field.is_skipped() {
+        return quote!(#member: #default,);
+    }
+
+    let value = match default {
+        Some(default) => quote! {
+            match #parameter_name {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => #default,
+            }
+        },
+        None if field.is_optional() => quote! {
+            match #parameter_name {
+                ::core::option::Option::Some(value) => value,
+                ::core::option::Option::None => ::core::option::Option::None,
+            }
+        },
+        None => {
+            let message = format!("missing field `{}`", parameter_name);
+            quote! {
+                match #parameter_name {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => ::core::panic!(#message),
+                }
+            }
+        },
+    };
+
+    quote! {
+        #member: #value,
+    }
+}
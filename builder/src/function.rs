@@ -0,0 +1,462 @@
+//! Expansion for the `#[builder]` attribute on functions.
+//!
+//! The parameters of the function become the fields of a hidden struct which
+//! goes through the same expansion as `#[derive(Builder)]`, so they are
+//! classified exactly like struct fields and accept the same field
+//! attributes. The builder also gets a `call` method that builds the hidden
+//! struct and passes its fields back to the function in order.
+//!
+//! An attribute on a method cannot add items next to its `impl` block, so
+//! associated functions are supported by putting `#[builder]` on the `impl`
+//! block as well as on each function in it that should get a builder. For a
+//! method taking `self`, the entry point takes the same receiver and stores
+//! it in the builder, and `call` passes it back to the method.
+//!
+//! Struct fields cannot elide lifetimes, so every elided lifetime in a
+//! parameter type, e.g. in `&str`, becomes a lifetime parameter of the hidden
+//! struct and its builder. A lifetime that is left out of a path entirely, as
+//! in `Cow<str>`, is invisible to the macro and must be written as
+//! `Cow<'_, str>`. `impl Trait` parameters are not supported, as a
+//! field cannot have an `impl Trait` type. A skipped `PhantomData` field
+//! covers the generics that no parameter uses, such as a type parameter that
+//! only appears in the return type.
+
+use proc_macro2::{Group, TokenStream, TokenTree};
+use quote::{quote, format_ident, ToTokens};
+use syn::{
+    FnArg, GenericArgument, GenericParam, Generics, ImplItem, Item, ItemFn, ItemImpl, Lifetime, LifetimeDef, Pat,
+    PathArguments, Signature, Type, TypeParamBound, Visibility,
+};
+
+use crate::attributes::{self, find_attribute};
+use crate::{camel_case, StructInfo};
+
+pub fn expand(args: TokenStream, input: TokenStream) -> TokenStream {
+    let result = match syn::parse2(input) {
+        Ok(Item::Fn(item)) => expand_fn(args, item),
+        Ok(Item::Impl(item)) => expand_impl(args, item),
+        Ok(item) => Err(syn::Error::new_spanned(
+            item,
+            "#[builder] can only be placed on a function or an impl block",
+        )),
+        Err(error) => Err(error),
+    };
+
+    result.unwrap_or_else(|error| error.to_compile_error())
+}
+
+fn expand_fn(args: TokenStream, mut item: ItemFn) -> syn::Result<TokenStream> {
+    let fn_name = item.sig.ident.clone();
+    let entry_name = format_ident!("{}_builder", fn_name);
+    let vis = &item.vis;
+
+    let function = Function {
+        args,
+        name: camel_case(&fn_name),
+        generics: item.sig.generics.clone(),
+        callee: quote!(#fn_name),
+        self_ty: None,
+    };
+    let (builder, entry) = function.expand(vis, &mut item.sig, &entry_name)?;
+
+    Ok(quote! {
+        #item
+
+        #entry
+
+        #builder
+    })
+}
+
+fn expand_impl(args: TokenStream, mut item: ItemImpl) -> syn::Result<TokenStream> {
+    if !args.is_empty() {
+        return Err(syn::Error::new_spanned(args, "expected `#[builder]` on an impl block"));
+    }
+    if let Some((_, path, _)) = &item.trait_ {
+        return Err(syn::Error::new_spanned(path, "#[builder] cannot be placed on a trait impl"));
+    }
+
+    let self_ty = item.self_ty.clone();
+    let self_name = match &*self_ty {
+        Type::Path(type_path) => type_path.path.segments.last().map(|segment| segment.ident.clone()),
+        _ => None,
+    }
+    .ok_or_else(|| syn::Error::new_spanned(&self_ty, "#[builder] requires an impl block for a named type"))?;
+
+    let mut builders = Vec::new();
+    let mut entries = Vec::new();
+    let mut errors = Vec::new();
+
+    for impl_item in &mut item.items {
+        let method = match impl_item {
+            ImplItem::Method(method) => method,
+            _ => continue,
+        };
+        let position = match method.attrs.iter().position(|attr| attr.path.is_ident("builder")) {
+            Some(position) => position,
+            None => continue,
+        };
+        let attr = method.attrs.remove(position);
+        let args = if attr.tokens.is_empty() {
+            TokenStream::new()
+        } else {
+            match attr.parse_args() {
+                Ok(args) => args,
+                Err(error) => {
+                    errors.push(error);
+                    continue;
+                },
+            }
+        };
+
+        let method_name = method.sig.ident.clone();
+        let entry_name = format_ident!("{}_builder", method_name);
+        let mut generics = item.generics.clone();
+        for param in &method.sig.generics.params {
+            add_param(&mut generics, param.clone());
+        }
+        if let Some(where_clause) = &method.sig.generics.where_clause {
+            generics.make_where_clause().predicates.extend(where_clause.predicates.iter().cloned());
+        }
+
+        let function = Function {
+            args,
+            name: format!("{}{}", self_name, camel_case(&method_name)),
+            generics,
+            callee: quote!(<#self_ty>::#method_name),
+            self_ty: Some(&self_ty),
+        };
+
+        match function.expand(&method.vis, &mut method.sig, &entry_name) {
+            Ok((builder, entry)) => {
+                builders.push(builder);
+                entries.push(entry);
+            },
+            Err(error) => errors.push(error),
+        }
+    }
+
+    attributes::combine(errors)?;
+
+    for entry in entries {
+        item.items.push(syn::parse2(entry)?);
+    }
+
+    Ok(quote! {
+        #item
+
+        #(#builders)*
+    })
+}
+
+struct Function<'a> {
+    args: TokenStream,
+    name: String,
+    generics: Generics,
+    callee: TokenStream,
+    self_ty: Option<&'a Type>,
+}
+
+impl Function<'_> {
+    fn expand(
+        &self,
+        vis: &Visibility,
+        sig: &mut Signature,
+        entry_name: &syn::Ident,
+    ) -> syn::Result<(TokenStream, TokenStream)> {
+        let mut errors = Vec::new();
+        let mut fields = Vec::new();
+        let mut parameter_names = Vec::new();
+        let mut lifetimes = Vec::new();
+        let mut entry_receiver = None;
+
+        for input in &mut sig.inputs {
+            let input = match input {
+                FnArg::Typed(input) if !is_self(&input.pat) => input,
+                input => {
+                    if self.self_ty.is_none() {
+                        errors.push(syn::Error::new_spanned(
+                            input,
+                            "#[builder] on a method requires #[builder] on its impl block as well",
+                        ));
+                        continue;
+                    }
+
+                    let (receiver, receiver_type) = self_parameter(input, &mut lifetimes, &mut errors);
+                    let receiver_type = self.replace_self(receiver_type);
+                    fields.push(quote! {
+                        #[builder(private, skip_cli)]
+                        __receiver: #receiver_type
+                    });
+                    parameter_names.push(format_ident!("__receiver"));
+                    entry_receiver = Some(receiver);
+                    continue;
+                },
+            };
+
+            let parameter_name = match &*input.pat {
+                Pat::Ident(pat) if pat.by_ref.is_none() && pat.subpat.is_none() => pat.ident.clone(),
+                pat => {
+                    errors.push(syn::Error::new_spanned(pat, "#[builder] parameters must be plain identifiers"));
+                    continue;
+                },
+            };
+
+            let (builder_attrs, attrs) = input.attrs
+                .drain(..)
+                .partition::<Vec<_>, _>(|attr| attr.path.is_ident("builder"));
+            input.attrs = attrs;
+
+            let mut parameter_type = (*input.ty).clone();
+            name_lifetimes(&mut parameter_type, &mut lifetimes, &mut errors);
+            let parameter_type = self.replace_self(parameter_type.to_token_stream());
+            fields.push(quote! {
+                #(#builder_attrs)*
+                #parameter_name: #parameter_type
+            });
+            parameter_names.push(parameter_name);
+        }
+
+        attributes::combine(errors)?;
+
+        let args = &self.args;
+        let container_attrs = if args.is_empty() {
+            Vec::new()
+        } else {
+            vec![syn::parse_quote!(#[builder(#args)])]
+        };
+        let has_name = find_attribute(
+            &attributes::parse(&container_attrs, attributes::CONTAINER_KEYS, &mut Vec::new()),
+            &["name"],
+        )
+        .is_some();
+        let name_attr = if has_name {
+            TokenStream::new()
+        } else {
+            let builder_name = format!("{}Builder", self.name);
+            quote!(#[builder(name = #builder_name)])
+        };
+
+        let mut generics = self.generics.clone();
+        let mut entry_generics = sig.generics.clone();
+        for lifetime in &lifetimes {
+            add_param(&mut generics, GenericParam::Lifetime(LifetimeDef::new(lifetime.clone())));
+            add_param(&mut entry_generics, GenericParam::Lifetime(LifetimeDef::new(lifetime.clone())));
+        }
+
+        let lifetime_params = generics.lifetimes().map(|param| &param.lifetime);
+        let type_params = generics.type_params().map(|param| &param.ident);
+        fields.push(quote! {
+            #[builder(skip, default = "::core::marker::PhantomData")]
+            __phantom: ::core::marker::PhantomData<fn() -> (#(&#lifetime_params (),)* #(*const #type_params,)*)>
+        });
+
+        let args_name = format_ident!("__{}Args", self.name);
+        let params = &generics.params;
+        let where_clause = &generics.where_clause;
+        let input = quote! {
+            #(#container_attrs)*
+            #name_attr
+            #[doc(hidden)]
+            #vis struct #args_name<#params> #where_clause {
+                #(#fields,)*
+            }
+        };
+
+        let mut input: syn::DeriveInput = syn::parse2(input)?;
+        let struct_infos = StructInfo::from_input(&input)?;
+        let struct_info = &struct_infos[0];
+        if let Some(tag) = struct_info.attributes.iter().find(|attr| attr.tag == "typestate") {
+            return Err(syn::Error::new_spanned(&tag.tag, "typestate builders are not supported for functions"));
+        }
+
+        let builder_name = struct_info.builder_ident();
+        let builder_fn_name = struct_info.builder_fn_ident();
+        let build_fn_name = struct_info.build_fn_ident();
+        let error_type = struct_info.error_type();
+        let receiver = struct_info.container().pattern.receiver();
+        let derived = crate::expand(struct_info);
+
+        // The `builder` attribute macro may be in scope where the hidden
+        // struct is emitted, so its attributes must not be left on it.
+        input.attrs.retain(|attr| !attr.path.is_ident("builder"));
+        if let syn::Data::Struct(data) = &mut input.data {
+            for field in &mut data.fields {
+                field.attrs.retain(|attr| !attr.path.is_ident("builder"));
+            }
+        }
+
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let entry_params = &entry_generics.params;
+        let output = match &sig.output {
+            syn::ReturnType::Default => quote!(()),
+            syn::ReturnType::Type(_, ty) => self.replace_self(ty.to_token_stream()),
+        };
+        let asyncness = &sig.asyncness;
+        let unsafety = &sig.unsafety;
+        let await_call = asyncness.map(|_| quote!(.await));
+        let callee = &self.callee;
+
+        let builder = quote! {
+            #input
+
+            #derived
+
+            impl #impl_generics #builder_name #ty_generics #where_clause {
+                #vis #asyncness #unsafety fn call(#receiver) -> ::core::result::Result<#output, #error_type> {
+                    let __args = self.#build_fn_name()?;
+                    ::core::result::Result::Ok(#callee(#(__args.#parameter_names),*)#await_call)
+                }
+            }
+        };
+
+        let entry = match entry_receiver {
+            Some(receiver) => quote! {
+                #vis fn #entry_name<#entry_params>(#receiver) -> #builder_name #ty_generics #where_clause {
+                    let mut __builder = <#args_name #ty_generics>::#builder_fn_name();
+                    __builder.__receiver = ::core::option::Option::Some(self);
+                    __builder
+                }
+            },
+            None => quote! {
+                #vis fn #entry_name<#entry_params>() -> #builder_name #ty_generics #where_clause {
+                    <#args_name #ty_generics>::#builder_fn_name()
+                }
+            },
+        };
+
+        Ok((builder, entry))
+    }
+
+    fn replace_self(&self, tokens: TokenStream) -> TokenStream {
+        let self_ty = match self.self_ty {
+            Some(self_ty) => self_ty,
+            None => return tokens,
+        };
+
+        // `Self` would name the builder inside its impl blocks.
+        tokens
+            .into_iter()
+            .map(|token| match token {
+                TokenTree::Ident(ident) if ident == "Self" => quote!(#self_ty),
+                TokenTree::Group(group) => {
+                    let mut replaced = Group::new(group.delimiter(), self.replace_self(group.stream()));
+                    replaced.set_span(group.span());
+                    TokenTree::Group(replaced).into()
+                },
+                token => token.into(),
+            })
+            .collect()
+    }
+}
+
+fn is_self(pat: &Pat) -> bool {
+    matches!(pat, Pat::Ident(pat) if pat.ident == "self")
+}
+
+// Returns the receiver for the entry point and the type it is stored as.
+fn self_parameter(
+    input: &FnArg,
+    lifetimes: &mut Vec<Lifetime>,
+    errors: &mut Vec<syn::Error>,
+) -> (TokenStream, TokenStream) {
+    match input {
+        FnArg::Receiver(receiver) => match &receiver.reference {
+            Some((and_token, lifetime)) => {
+                let lifetime = match lifetime {
+                    Some(lifetime) if lifetime.ident != "_" => lifetime.clone(),
+                    _ => fresh_lifetime(lifetimes, and_token.span),
+                };
+                let mutability = &receiver.mutability;
+                (quote!(&#lifetime #mutability self), quote!(&#lifetime #mutability Self))
+            },
+            None => (quote!(self), quote!(Self)),
+        },
+        FnArg::Typed(input) => {
+            let mut receiver_type = (*input.ty).clone();
+            name_lifetimes(&mut receiver_type, lifetimes, errors);
+            (quote!(self: #receiver_type), receiver_type.into_token_stream())
+        },
+    }
+}
+
+// Lifetime parameters have to come before the others.
+fn add_param(generics: &mut Generics, param: GenericParam) {
+    let index = match param {
+        GenericParam::Lifetime(_) => generics.lifetimes().count(),
+        _ => generics.params.len(),
+    };
+
+    generics.params.insert(index, param);
+}
+
+fn name_lifetimes(ty: &mut Type, lifetimes: &mut Vec<Lifetime>, errors: &mut Vec<syn::Error>) {
+    match ty {
+        Type::Reference(reference) => {
+            if reference.lifetime.as_ref().is_none_or(|lifetime| lifetime.ident == "_") {
+                let span = reference.and_token.span;
+                reference.lifetime = Some(fresh_lifetime(lifetimes, span));
+            }
+            name_lifetimes(&mut reference.elem, lifetimes, errors);
+        },
+        Type::Path(type_path) => {
+            if let Some(qself) = &mut type_path.qself {
+                name_lifetimes(&mut qself.ty, lifetimes, errors);
+            }
+            name_path_lifetimes(&mut type_path.path, lifetimes, errors);
+        },
+        Type::TraitObject(trait_object) => {
+            for bound in &mut trait_object.bounds {
+                match bound {
+                    TypeParamBound::Lifetime(lifetime) if lifetime.ident == "_" => {
+                        *lifetime = fresh_lifetime(lifetimes, lifetime.apostrophe);
+                    },
+                    TypeParamBound::Trait(bound) => name_path_lifetimes(&mut bound.path, lifetimes, errors),
+                    _ => {},
+                }
+            }
+        },
+        Type::Array(array) => name_lifetimes(&mut array.elem, lifetimes, errors),
+        Type::Slice(slice) => name_lifetimes(&mut slice.elem, lifetimes, errors),
+        Type::Ptr(ptr) => name_lifetimes(&mut ptr.elem, lifetimes, errors),
+        Type::Paren(paren) => name_lifetimes(&mut paren.elem, lifetimes, errors),
+        Type::Group(group) => name_lifetimes(&mut group.elem, lifetimes, errors),
+        Type::Tuple(tuple) => {
+            for elem in &mut tuple.elems {
+                name_lifetimes(elem, lifetimes, errors);
+            }
+        },
+        Type::ImplTrait(impl_trait) => errors.push(syn::Error::new_spanned(
+            impl_trait,
+            "#[builder] does not support `impl Trait` parameters, use a generic parameter instead",
+        )),
+        // Elided lifetimes in `fn` pointers belong to the pointer itself.
+        _ => {},
+    }
+}
+
+fn name_path_lifetimes(path: &mut syn::Path, lifetimes: &mut Vec<Lifetime>, errors: &mut Vec<syn::Error>) {
+    for segment in &mut path.segments {
+        let args = match &mut segment.arguments {
+            PathArguments::AngleBracketed(args) => args,
+            // The same goes for the arguments of `Fn(&str)` and friends.
+            _ => continue,
+        };
+
+        for arg in &mut args.args {
+            match arg {
+                GenericArgument::Lifetime(lifetime) if lifetime.ident == "_" => {
+                    *lifetime = fresh_lifetime(lifetimes, lifetime.apostrophe);
+                },
+                GenericArgument::Type(ty) => name_lifetimes(ty, lifetimes, errors),
+                _ => {},
+            }
+        }
+    }
+}
+
+fn fresh_lifetime(lifetimes: &mut Vec<Lifetime>, span: proc_macro2::Span) -> Lifetime {
+    let lifetime = Lifetime::new(&format!("'__{}", lifetimes.len()), span);
+    lifetimes.push(lifetime.clone());
+    lifetime
+}
@@ -1,70 +1,226 @@
+// The field and flag enums hold `syn::Type`s by value, a few at most per
+// field, for the duration of one expansion.
+#![allow(clippy::large_enum_variant)]
+
 extern crate proc_macro;
 
+mod attributes;
+mod cli;
+mod constant;
+mod function;
+mod merge;
+mod typestate;
+mod validate;
+
 use proc_macro::TokenStream;
-use syn::{Data, Attribute, Ident, Field, Type, DeriveInput, parse_macro_input};
-use quote::{quote, format_ident};
+use syn::{Data, Ident, Field, Fields, Generics, Member, Type, DeriveInput, parse_macro_input, parse_quote};
+use quote::{quote, quote_spanned, format_ident};
+use syn::ext::IdentExt;
+
+use attributes::{AttributeInfo, AttributeValue, find_attribute, find_metas};
 
 #[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
-    let struct_name = format_ident!("{}", input.ident);
-    let builder_name = format_ident!("{}Builder", input.ident);
+    let struct_infos = match StructInfo::from_input(&input) {
+        Ok(struct_infos) => struct_infos,
+        Err(error) => return error.to_compile_error().into(),
+    };
+
+    struct_infos
+        .iter()
+        .map(expand)
+        .collect::<proc_macro2::TokenStream>()
+        .into()
+}
+
+#[proc_macro_attribute]
+pub fn builder(args: TokenStream, input: TokenStream) -> TokenStream {
+    function::expand(args.into(), input.into()).into()
+}
+
+fn expand(struct_info: &StructInfo) -> proc_macro2::TokenStream {
+    if struct_info.has_flag("typestate") {
+        return typestate::expand(struct_info);
+    }
+
+    let struct_name = &struct_info.ident;
+    let builder_fn_name = struct_info.builder_fn_ident();
+    let constructor = struct_info.constructor();
+    let builder_name = struct_info.builder_ident();
+    let error_name = struct_info.error_ident();
+    let error_type = struct_info.error_type();
 
-    let struct_info = StructInfo::from(&input);
+    let generics = &struct_info.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let clone_bounds = struct_info.clone_bounds();
+    let container = struct_info.container();
+    let vis = container.vis();
+    let build_fn_name = struct_info.build_fn_ident();
+    let alloc = container.alloc();
+    let error_trait = container.error_trait();
+    let pattern = container.pattern;
+    let build_receiver = pattern.receiver();
+    let builder_attributes = struct_info.builder_attributes(pattern == Pattern::Immutable);
 
     let field_definitions = data_from_fields(&struct_info.fields, FieldInfo::field_definition);
     let default_builders = data_from_fields(&struct_info.fields, FieldInfo::default_builder);
     let setters = data_from_fields(&struct_info.fields, FieldInfo::setter);
+    let env_lookups = data_from_fields(&struct_info.fields, |field| field.env_lookup(&error_name));
+    let sub_builds = data_from_fields(&struct_info.fields, FieldInfo::sub_build);
     let validations = data_from_fields(&struct_info.fields, FieldInfo::validation);
     let each_builders = data_from_fields(&struct_info.fields, FieldInfo::each);
+    let sub_builders = data_from_fields(&struct_info.fields, FieldInfo::sub_builder_mut);
+    let accessors = data_from_fields(&struct_info.fields, FieldInfo::accessors);
     let field_builders = data_from_fields(&struct_info.fields, FieldInfo::build);
+    let struct_default = struct_info.struct_default();
+    let to_builder = struct_info.to_builder();
+    let by_reference = struct_info.by_reference();
+    let (sub_builder_items, sub_builder_checks) = struct_info.sub_builder_checks();
+    let checks = match validate::expand(struct_info) {
+        Ok(checks) => checks,
+        Err(error) => return error.to_compile_error(),
+    };
+    let merge = match merge::expand(struct_info) {
+        Ok(merge) => merge,
+        Err(error) => return error.to_compile_error(),
+    };
+    let parse_args = match cli::expand(struct_info) {
+        Ok(parse_args) => parse_args,
+        Err(error) => return error.to_compile_error(),
+    };
+    let build_const = match constant::expand(struct_info) {
+        Ok(build_const) => build_const,
+        Err(error) => return error.to_compile_error(),
+    };
+    let constness = container.constness();
+    let (error_variants, error_display) = struct_info.error_variants();
+    let missing_field_type = struct_info.missing_field_type();
 
-    for builder in &each_builders {
-        println!("{}", builder);
-    }
-    println!("--");
+    let (violations, report_violations) = if struct_info.has_violations() {
+        (
+            quote!(let mut violations: #alloc::vec::Vec<#alloc::string::String> = #alloc::vec::Vec::new();),
+            // Missing fields are listed with the violations so that one error
+            // reports everything that is wrong with the builder.
+            quote! {
+                if !violations.is_empty() {
+                    let mut report: #alloc::vec::Vec<#alloc::string::String> = #alloc::vec::Vec::new();
+                    for field in &missing_fields {
+                        report.push(#alloc::format!("missing field `{}`", field));
+                    }
+                    report.append(&mut violations);
+                    return ::core::result::Result::Err(#error_name::ValidationFailed(report));
+                }
+            },
+        )
+    } else {
+        (proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new())
+    };
+
+    // `build` converts into a custom `build_fn(error = "...")` type, parents
+    // holding this builder as a `sub_builder` call `__build` instead.
+    let build_call = match struct_info.attribute(&["build_fn", "error"]) {
+        Some(_) => quote!(::core::result::Result::map_err(self.__build(), ::core::convert::Into::into)),
+        None => quote!(self.__build()),
+    };
 
     let result = quote! {
-        pub struct #builder_name {
+        #[derive(::core::fmt::Debug, ::core::clone::Clone, ::core::cmp::PartialEq, ::core::cmp::Eq)]
+        #vis enum #error_name {
+            #error_variants
+        }
+
+        impl ::core::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                match self {
+                    #error_display
+                }
+            }
+        }
+
+        impl #error_trait for #error_name {}
+
+        #builder_attributes
+        #vis struct #builder_name #generics #where_clause {
             #(#field_definitions)*
+            // Skipped fields may be the only ones using a generic parameter.
+            __marker: ::core::marker::PhantomData<fn() -> #struct_name #ty_generics>,
         }
 
-        impl #struct_name {
-            pub fn builder() -> #builder_name {
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #vis #constness fn #builder_fn_name() -> #builder_name #ty_generics {
                 #builder_name {
                     #(#default_builders)*
+                    __marker: ::core::marker::PhantomData,
                 }
             }
         }
 
-        impl #builder_name {
-            pub fn build(&mut self) -> Result<#struct_name, Box<dyn std::error::Error>> {
-                let mut missing_fields = Vec::new();
+        impl #impl_generics #builder_name #ty_generics #where_clause {
+            #vis fn #build_fn_name(#build_receiver) -> ::core::result::Result<#struct_name #ty_generics, #error_type>
+            #clone_bounds
+            {
+                #build_call
+            }
+
+            #[doc(hidden)]
+            #vis fn __build(#build_receiver) -> ::core::result::Result<#struct_name #ty_generics, #error_name>
+            #clone_bounds
+            {
+                #sub_builder_checks
+                #(#env_lookups)*
+
+                let mut missing_fields: #alloc::vec::Vec<#missing_field_type> = #alloc::vec::Vec::new();
+                #violations
 
                 #(#validations)*
 
-                if missing_fields.is_empty() {
-                    Ok(#struct_name {
-                        #(#field_builders)*
-                    })
-                } else {
-                    Err(format!("Missing fields: {}", missing_fields.join(",")).into())
+                #checks
+
+                #(#sub_builds)*
+
+                #report_violations
+
+                if !missing_fields.is_empty() {
+                    return ::core::result::Result::Err(#error_name::MissingFields(missing_fields));
                 }
+
+                #struct_default
+
+                ::core::result::Result::Ok(#constructor {
+                    #(#field_builders)*
+                })
             }
 
+            #by_reference
+
+            #build_const
+
+            #merge
+
+            #parse_args
+
             #(#setters)*
 
             #(#each_builders)*
+
+            #(#sub_builders)*
+
+            #(#accessors)*
         }
+
+        #to_builder
+
+        #sub_builder_items
     };
 
     // eprintln!("Output: {:#?}", &result);
 
-    result.into()
+    result
 }
 
-fn data_from_fields<F>(fields: &[FieldInfo], data_extractor: F) -> Vec<proc_macro2::TokenStream> 
+fn data_from_fields<F>(fields: &[FieldInfo], data_extractor: F) -> Vec<proc_macro2::TokenStream>
 where F: Fn(&FieldInfo) -> proc_macro2::TokenStream
 {
     fields
@@ -76,246 +232,1743 @@ where F: Fn(&FieldInfo) -> proc_macro2::TokenStream
 #[derive(Debug)]
 struct StructInfo {
     pub ident: Ident,
+    pub variant: Option<Ident>,
+    pub vis: syn::Visibility,
+    pub generics: Generics,
     pub fields: Vec<FieldInfo>,
+    pub attributes: Vec<AttributeInfo>,
 }
 
-impl From<&DeriveInput> for StructInfo {
-    fn from(input: &DeriveInput) -> Self {
-        let fields = match &input.data {
-            Data::Struct(struct_data) => {
-                struct_data.fields
+impl StructInfo {
+    pub fn from_input(input: &DeriveInput) -> syn::Result<Vec<Self>> {
+        let mut errors = Vec::new();
+        let attributes = attributes::parse(&input.attrs, attributes::CONTAINER_KEYS, &mut errors);
+        Self::check(&attributes, &mut errors);
+
+        let struct_infos = match &input.data {
+            Data::Struct(struct_data) => match &struct_data.fields {
+                Fields::Unit => {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "Builder cannot be derived for unit structs",
+                    ));
+                },
+                fields => vec![Self::new(input, &attributes, None, fields, &mut errors)],
+            },
+            Data::Enum(enum_data) => {
+                let struct_default = attributes
+                    .iter()
+                    .find(|attr| attr.tag == "default" && attr.value == AttributeValue::Flag);
+                if let Some(attr) = struct_default {
+                    errors.push(syn::Error::new_spanned(
+                        &attr.tag,
+                        "#[builder(default)] on an enum is not supported, put it on individual fields",
+                    ));
+                }
+
+                if let Some(attr) = attributes.iter().find(|attr| attr.tag == "name") {
+                    errors.push(syn::Error::new_spanned(
+                        &attr.tag,
+                        "#[builder(name = \"...\")] on an enum would give every variant's builder the same name, \
+                         put it on individual variants",
+                    ));
+                }
+
+                let struct_infos: Vec<Self> = enum_data.variants
                     .iter()
-                    .map(FieldInfo::new)
-                    .flatten()
-                    .collect()
+                    .filter(|variant| !matches!(variant.fields, Fields::Unit))
+                    .map(|variant| Self::new(input, &attributes, Some(variant), &variant.fields, &mut errors))
+                    .collect();
+
+                if struct_infos.is_empty() {
+                    return Err(syn::Error::new_spanned(
+                        &input.ident,
+                        "Builder requires at least one enum variant with fields",
+                    ));
+                }
+
+                struct_infos
+            },
+            Data::Union(union_data) => {
+                return Err(syn::Error::new_spanned(
+                    union_data.union_token,
+                    "Builder cannot be derived for unions",
+                ));
             },
-            _ => Vec::new(),
         };
 
+        attributes::combine(errors)?;
+
+        Ok(struct_infos)
+    }
+
+    fn check(attributes: &[AttributeInfo], errors: &mut Vec<syn::Error>) {
+        let tag = |name: &str| &attributes.iter().find(|attr| attr.tag == name).unwrap().tag;
+
+        if let Some(AttributeValue::Str(crate_path)) = find_attribute(attributes, &["crate"]) {
+            if let Err(error) = syn::parse_str::<syn::Path>(crate_path) {
+                errors.push(syn::Error::new_spanned(tag("crate"), format!("invalid crate path: {}", error)));
+            }
+        }
+
+        if let Some(AttributeValue::Str(vis)) = find_attribute(attributes, &["vis"]) {
+            if let Err(error) = syn::parse_str::<syn::Visibility>(vis) {
+                errors.push(syn::Error::new_spanned(tag("vis"), format!("invalid visibility: {}", error)));
+            }
+        }
+
+        if let Some(AttributeValue::Str(error_type)) = find_attribute(attributes, &["build_fn", "error"]) {
+            if let Err(error) = syn::parse_str::<Type>(error_type) {
+                errors.push(syn::Error::new_spanned(tag("build_fn"), format!("invalid error type: {}", error)));
+            }
+        }
+    }
+
+    fn new(
+        input: &DeriveInput,
+        container_attributes: &[AttributeInfo],
+        variant: Option<&syn::Variant>,
+        fields: &Fields,
+        errors: &mut Vec<syn::Error>,
+    ) -> Self {
+        let mut attributes = container_attributes.to_vec();
+        if let Some(variant) = variant {
+            let variant_attributes = attributes::parse(&variant.attrs, attributes::CONTAINER_KEYS, errors);
+            Self::check(&variant_attributes, errors);
+            attributes.extend(variant_attributes);
+        }
+
+        let mut container = ContainerFlags::new(&attributes);
+        if container.vis.is_none() {
+            container.vis = Some(input.vis.clone());
+        }
+
+        let fields: Vec<FieldInfo> = fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| FieldInfo {
+                container: container.clone(),
+                ..FieldInfo::new(index, field, errors)
+            })
+            .collect();
+
+        let no_std = attributes.iter().find(|attr| attr.tag == "no_std");
+        if let Some(no_std) = no_std.filter(|_| fields.iter().any(|field| field.env_var().is_some())) {
+            errors.push(syn::Error::new_spanned(
+                &no_std.tag,
+                "`no_std` builders cannot read fields from the environment, remove `env` and `env_prefix`",
+            ));
+        }
+
         Self {
             ident: input.ident.clone(),
+            variant: variant.map(|variant| variant.ident.clone()),
+            vis: input.vis.clone(),
+            generics: input.generics.clone(),
             fields,
+            attributes,
         }
     }
-}
 
-#[derive(Debug)]
-enum SpecialField {
-    Vec(Type),
-    Option(Type),
-}
+    pub fn builder_ident(&self) -> Ident {
+        if let Some(name) = self.attribute(&["name"]).and_then(AttributeValue::as_str) {
+            return format_ident!("{}", name);
+        }
 
-#[derive(Debug)]
-struct FieldInfo {
-    pub ident: Ident,
-    pub ty: Type,
-    pub special_field: Option<SpecialField>,
-    pub attributes: Vec<AttributeInfo>
-}
+        match &self.variant {
+            Some(variant) => format_ident!("{}{}Builder", self.ident, variant),
+            None => format_ident!("{}Builder", self.ident),
+        }
+    }
 
-impl FieldInfo {
-    pub fn new(field: &Field) -> Option<Self> {
-        let attributes = field.attrs
+    pub fn error_ident(&self) -> Ident {
+        format_ident!("{}Error", self.builder_ident())
+    }
+
+    pub fn builder_fn_ident(&self) -> Ident {
+        match &self.variant {
+            Some(variant) => format_ident!("{}_builder", snake_case(variant)),
+            None => format_ident!("builder"),
+        }
+    }
+
+    pub fn constructor(&self) -> proc_macro2::TokenStream {
+        let struct_name = &self.ident;
+
+        match &self.variant {
+            Some(variant) => quote!(#struct_name::#variant),
+            None => quote!(#struct_name),
+        }
+    }
+
+    pub fn attribute(&self, path: &[&str]) -> Option<&AttributeValue> {
+        find_attribute(&self.attributes, path)
+    }
+
+    pub fn has_flag(&self, tag: &str) -> bool {
+        self.attribute(&[tag]) == Some(&AttributeValue::Flag)
+    }
+
+    pub fn builder_attributes(&self, derive_clone: bool) -> proc_macro2::TokenStream {
+        let mut derives: Vec<syn::Path> = self.attributes
             .iter()
-            .map(AttributeInfo::new)
-            .flatten()
+            .filter(|attr| attr.tag == "derive")
+            .flat_map(|attr| match &attr.value {
+                AttributeValue::Paths(paths) => paths.clone(),
+                _ => Vec::new(),
+            })
             .collect();
 
-        match &field.ident {
-            Some(ident) => {
-                Some(Self {
-                    ident: ident.clone(),
-                    ty: field.ty.clone(),
-                    special_field: special_field_info(&field),
-                    attributes,
-                })        
+        if derive_clone {
+            derives.retain(|path| path.segments.last().is_none_or(|segment| segment.ident != "Clone"));
+            derives.insert(0, parse_quote!(::core::clone::Clone));
+        }
+
+        let derives = if derives.is_empty() {
+            proc_macro2::TokenStream::new()
+        } else {
+            quote!(#[derive(#(#derives),*)])
+        };
+        let struct_attrs = find_metas(&self.attributes, "struct_attr");
+
+        quote! {
+            #derives
+            #(#[#struct_attrs])*
+        }
+    }
+
+    // Checks and sub-builders report everything but missing fields as
+    // violations.
+    pub fn has_violations(&self) -> bool {
+        validate::has_checks(self) || self.fields.iter().any(FieldInfo::is_sub_builder)
+    }
+
+    // Variants other than `MissingFields` are only added when the builder
+    // can produce them, so matching on the error of a builder that does not
+    // use env fallbacks, checks, sub-builders or `cli` needs no other arms.
+    pub fn error_variants(&self) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        let error_name = self.error_ident();
+        let alloc = self.container().alloc();
+        let missing_field_type = self.missing_field_type();
+
+        let mut variants = quote! {
+            MissingFields(#alloc::vec::Vec<#missing_field_type>),
+        };
+        let mut display = quote! {
+            #error_name::MissingFields(fields) => {
+                ::core::write!(f, "missing fields: {}", fields.join(", "))
             },
-            None => None,
+        };
+
+        if self.has_violations() {
+            variants.extend(quote! {
+                ValidationFailed(#alloc::vec::Vec<#alloc::string::String>),
+            });
+            display.extend(quote! {
+                #error_name::ValidationFailed(violations) => {
+                    ::core::write!(f, "validation failed: {}", violations.join("; "))
+                },
+            });
+        }
+
+        if self.fields.iter().any(|field| field.env_var().is_some()) {
+            variants.extend(quote! {
+                InvalidEnvVar {
+                    variable: &'static str,
+                    error: #alloc::string::String,
+                },
+            });
+            display.extend(quote! {
+                #error_name::InvalidEnvVar { variable, error } => {
+                    ::core::write!(f, "invalid value in environment variable {}: {}", variable, error)
+                },
+            });
         }
+
+        let (cli_variants, cli_display) = cli::error_variants(self);
+        variants.extend(cli_variants);
+        display.extend(cli_display);
+
+        (variants, display)
     }
 
-    pub fn field_definition(&self) -> proc_macro2::TokenStream {
-        let parameter_name = &self.ident;
-        let parameter_type = &self.ty;
+    // Missing fields of sub-builders are reported by their path, e.g.
+    // `server.port`, which has to be built at runtime.
+    pub fn missing_field_type(&self) -> proc_macro2::TokenStream {
+        if self.fields.iter().any(FieldInfo::is_sub_builder) {
+            let alloc = self.container().alloc();
+            quote!(#alloc::string::String)
+        } else {
+            quote!(&'static str)
+        }
+    }
+
+    pub fn error_type(&self) -> Type {
+        let error_name = self.error_ident();
+
+        self.attribute(&["build_fn", "error"])
+            .and_then(AttributeValue::as_str)
+            .and_then(|value| syn::parse_str(value).ok())
+            .unwrap_or_else(|| syn::parse_quote!(#error_name))
+    }
+
+    pub fn struct_default(&self) -> proc_macro2::TokenStream {
+        if !self.has_flag("default") {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let struct_name = &self.ident;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
 
         quote! {
-            #parameter_name: Option<#parameter_type>,
-        }    
+            let __default: #struct_name #ty_generics = ::core::default::Default::default();
+        }
     }
 
-    pub fn default_builder(&self) -> proc_macro2::TokenStream {
-        let parameter_name = &self.ident;
+    pub fn generic_args(&self) -> Vec<proc_macro2::TokenStream> {
+        use syn::GenericParam;
 
-        match self.special_field {
-            Some(SpecialField::Vec(_)) => quote! {
-                #parameter_name: Vec::new(),
-            },
-            _ => quote! {
-                #parameter_name: None,
-            } 
+        self.generics.params
+            .iter()
+            .map(|param| match param {
+                GenericParam::Lifetime(lifetime) => {
+                    let lifetime = &lifetime.lifetime;
+                    quote!(#lifetime)
+                },
+                GenericParam::Type(type_param) => {
+                    let ident = &type_param.ident;
+                    quote!(#ident)
+                },
+                GenericParam::Const(const_param) => {
+                    let ident = &const_param.ident;
+                    quote!(#ident)
+                },
+            })
+            .collect()
+    }
+
+    pub fn container(&self) -> ContainerFlags {
+        let mut container = ContainerFlags::new(&self.attributes);
+        if container.vis.is_none() {
+            container.vis = Some(self.vis.clone());
         }
+
+        container
     }
 
-    pub fn setter(&self) -> proc_macro2::TokenStream {
-        let parameter_name = &self.ident;
-        let parameter_type = &self.ty;
+    pub fn build_fn_ident(&self) -> Ident {
+        self.attribute(&["build_fn", "name"])
+            .and_then(AttributeValue::as_str)
+            .map(|name| format_ident!("{}", name))
+            .unwrap_or_else(|| format_ident!("build"))
+    }
 
-        match &self.special_field  {
-            Some(SpecialField::Vec(_)) => proc_macro2::TokenStream::new(),
-            _ => {
-                quote! {
-                    pub fn #parameter_name(&mut self, #parameter_name: #parameter_type) -> &mut Self {
-                        self.#parameter_name = Some(#parameter_name);
-                        self
-                    }
-                }
-            },
+    pub fn pattern(&self) -> Pattern {
+        self.container().pattern
+    }
+
+    pub fn clone_bounds(&self) -> proc_macro2::TokenStream {
+        self.bounds(self.cloned_types(), true)
+    }
+
+    // Typestate builders move their fields, only the struct's default is
+    // cloned from.
+    pub fn default_bounds(&self) -> proc_macro2::TokenStream {
+        self.bounds(self.default_types(), true)
+    }
+
+    fn bounds(&self, cloned_types: Vec<&Type>, struct_default: bool) -> proc_macro2::TokenStream {
+        let mut bounds: Vec<proc_macro2::TokenStream> = cloned_types
+            .iter()
+            .map(|ty| quote!(#ty: ::core::clone::Clone))
+            .collect();
+
+        if struct_default && self.has_flag("default") && self.generics.type_params().next().is_some() {
+            let struct_name = &self.ident;
+            let (_, ty_generics, _) = self.generics.split_for_impl();
+            bounds.push(quote!(#struct_name #ty_generics: ::core::default::Default));
+        }
+
+        if bounds.is_empty() {
+            return proc_macro2::TokenStream::new();
+        }
+
+        quote! {
+            where #(#bounds),*
         }
     }
 
-    pub fn validation(&self) -> proc_macro2::TokenStream {
-        match &self.special_field {
-            Some(SpecialField::Option(_)) => {
-                let parameter_name = &self.ident;
+    // Field types that need a `Clone` bound because the builder clones them.
+    fn cloned_types(&self) -> Vec<&Type> {
+        if self.generics.type_params().next().is_none() {
+            return Vec::new();
+        }
 
-                quote! {
-                    if self.#parameter_name.is_none() {
-                        missing_fields.push(stringify!(#parameter_name));
-                    }
-                }
-            },
-            _ => proc_macro2::TokenStream::new(),
+        if self.pattern() == Pattern::Owned {
+            return self.default_types();
         }
+
+        self.fields
+            .iter()
+            .filter(|field| !field.is_sub_builder())
+            .map(|field| &field.ty)
+            .collect()
     }
 
-    pub fn each(&self) -> proc_macro2::TokenStream {
-        match &self.special_field {
-            Some(SpecialField::Vec(inner_type)) => {
-                let parameter_name = &self.ident;
-        
-                let each_attr = &self.attributes
-                    .iter()
-                    .find(|attr| attr.tag == "each");
+    fn default_types(&self) -> Vec<&Type> {
+        if self.generics.type_params().next().is_none() {
+            return Vec::new();
+        }
 
-                match each_attr {
-                    Some(each_attr) => {
-                        let function_name = format_ident!("{}", &each_attr.value);
+        self.fields
+            .iter()
+            .filter(|field| field.takes_struct_default())
+            .map(|field| &field.ty)
+            .collect()
+    }
 
-                        quote! {
-                            pub fn #function_name(&mut self, #function_name: #inner_type) -> &mut Self {
-                                self.#parameter_name.push(#function_name);
-                                self
-                            }
-                        }
-                    },
-                    None => proc_macro2::TokenStream::new(),
+    // Parents holding this builder as a `sub_builder` build and recreate it
+    // through these. Owned builders cannot do either through a reference,
+    // their parents assert on `__BUILDS_BY_REFERENCE` before using the stubs.
+    pub fn by_reference(&self) -> proc_macro2::TokenStream {
+        if self.variant.is_some() {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let struct_name = &self.ident;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        let error_name = self.error_ident();
+        let vis = self.container().vis();
+        let result = quote!(::core::result::Result<#struct_name #ty_generics, #error_name>);
+
+        if self.pattern() == Pattern::Owned {
+            return quote! {
+                #[doc(hidden)]
+                #vis const __BUILDS_BY_REFERENCE: bool = false;
+
+                #[doc(hidden)]
+                #vis fn __build_by_reference(&mut self) -> #result {
+                    ::core::unreachable!()
                 }
-            },
-            _ => proc_macro2::TokenStream::new(),
+
+                #[doc(hidden)]
+                #vis fn __from_reference(_: &#struct_name #ty_generics) -> Self {
+                    ::core::unreachable!()
+                }
+            };
         }
-    }
 
-    pub fn build(&self) -> proc_macro2::TokenStream {
-        let parameter_name = &self.ident;
+        let clone_bounds = self.clone_bounds();
+        let to_builder_bounds = self.bounds(self.cloned_types(), false);
 
-        if let Some(SpecialField::Option(_)) = self.special_field {
-            quote! {
-                #parameter_name: self.#parameter_name.clone(),
+        quote! {
+            #[doc(hidden)]
+            #vis const __BUILDS_BY_REFERENCE: bool = true;
+
+            #[doc(hidden)]
+            #vis fn __build_by_reference(&mut self) -> #result
+            #clone_bounds
+            {
+                self.__build()
             }
-        } else {
-            quote! {
-                #parameter_name: self.#parameter_name.clone().unwrap(),
+
+            #[doc(hidden)]
+            #vis fn __from_reference(value: &#struct_name #ty_generics) -> Self
+            #to_builder_bounds
+            {
+                value.to_builder()
             }
         }
     }
-}
 
-#[derive(Debug)]
-struct AttributeInfo {
-    pub ident: Ident,
-    pub tag:  Ident,
-    pub value: String,
-}
+    // A builder that builds by reference cannot hold an owned one, and only
+    // the nested builder knows its pattern. The assertions go at module level
+    // where `cargo check` evaluates them, or into the methods using the
+    // nested builder when its type may depend on generic parameters.
+    pub fn sub_builder_checks(&self) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        if self.pattern() == Pattern::Owned {
+            return (proc_macro2::TokenStream::new(), proc_macro2::TokenStream::new());
+        }
 
-impl AttributeInfo {
-    pub fn new(attribute: &Attribute) -> Option<Self> {
-        use proc_macro2::TokenTree;
-        use syn::PathSegment;
+        let builder_name = self.builder_ident();
+        let assertions: Vec<proc_macro2::TokenStream> = self.fields
+            .iter()
+            .filter_map(|field| {
+                let sub_builder_type = field.sub_builder_type()?;
+                let nested_name = &sub_builder_type.segments.last()?.ident;
+                let message = format!(
+                    "`{}` cannot be a sub-builder of `{}`: `{}` uses the owned pattern, \
+                     so it can only be nested in owned builders",
+                    field.ident.unraw(), builder_name, nested_name,
+                );
+                Some(quote_spanned! {field.sub_builder_span()=>
+                    ::core::assert!(<#sub_builder_type>::__BUILDS_BY_REFERENCE, #message)
+                })
+            })
+            .collect();
 
-        let ident = match &attribute.path.segments.iter().next() {
-            Some(PathSegment { ident, .. }) => ident.clone(),
-            _ => return None,
-        };
+        if self.generics.params.is_empty() {
+            (quote!(#(const _: () = #assertions;)*), proc_macro2::TokenStream::new())
+        } else {
+            (proc_macro2::TokenStream::new(), quote!(#(const { #assertions };)*))
+        }
+    }
 
-        let (tag, value) = match attribute.tokens.clone().into_iter().next() {
-            Some(TokenTree::Group(group)) => {
-                let tokens: Vec<TokenTree> = group
-                    .stream()
-                    .into_iter()
-                    .collect();
+    pub fn to_builder(&self) -> proc_macro2::TokenStream {
+        if self.variant.is_some() {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let struct_name = &self.ident;
+        let builder_name = self.builder_ident();
+        let vis = self.container().vis();
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+
+        let moved_fields = data_from_fields(&self.fields, |field| {
+            let member = &field.member;
+            field.unbuild(quote!(value.#member))
+        });
+        let cloned_fields = data_from_fields(&self.fields, |field| {
+            let member = &field.member;
+            if field.is_sub_builder() {
+                let parameter_name = &field.ident;
+                let sub_builder_type = field.sub_builder_type();
+                quote_spanned!(field.sub_builder_span()=> #parameter_name: <#sub_builder_type>::__from_reference(&self.#member),)
+            } else {
+                field.unbuild(quote!(::core::clone::Clone::clone(&self.#member)))
+            }
+        });
 
-                match &tokens.as_slice() {
-                    &[TokenTree::Ident(ident), TokenTree::Punct(punct), TokenTree::Literal(literal)] => {
-                        if punct.as_char() == '=' {
-                            (ident.clone(), literal.to_string().replace("\"", ""))
-                        } else {
-                            return None
+        if self.pattern() == Pattern::Owned {
+            return quote! {
+                impl #impl_generics ::core::convert::From<#struct_name #ty_generics> for #builder_name #ty_generics #where_clause {
+                    fn from(value: #struct_name #ty_generics) -> Self {
+                        #builder_name {
+                            #(#moved_fields)*
+                            __marker: ::core::marker::PhantomData,
                         }
-                    },
-                    _ => return None,
+                    }
                 }
+            };
+        }
+
+        let clone_bounds = self.bounds(self.cloned_types(), false);
+        let (_, sub_builder_checks) = self.sub_builder_checks();
+
+        // Builders that clone their fields anyway convert by cloning as well,
+        // moving fields out would not compile for structs implementing `Drop`.
+        let mut from_generics = self.generics.clone();
+        let cloned_types = self.cloned_types();
+        if !cloned_types.is_empty() {
+            let predicates = from_generics.make_where_clause();
+            for ty in cloned_types {
+                predicates.predicates.push(parse_quote!(#ty: ::core::clone::Clone));
             }
-            _ => return None,
-        };
+        }
+        let (_, _, from_where_clause) = from_generics.split_for_impl();
 
-        Some(Self {
-            ident,
-            tag,
-            value,
-        })
+        quote! {
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #vis fn to_builder(&self) -> #builder_name #ty_generics
+                #clone_bounds
+                {
+                    #sub_builder_checks
+                    #builder_name {
+                        #(#cloned_fields)*
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+
+            impl #impl_generics ::core::convert::From<#struct_name #ty_generics> for #builder_name #ty_generics #from_where_clause {
+                fn from(value: #struct_name #ty_generics) -> Self {
+                    value.to_builder()
+                }
+            }
+        }
     }
 }
 
-fn special_field_info(field: &Field) -> Option<SpecialField> {
-    use syn::{Path, TypePath, PathArguments, GenericArgument};
+#[derive(Debug, Clone, Default)]
+struct ContainerFlags {
+    pub default: bool,
+    pub setter_into: bool,
+    pub try_setter: bool,
+    pub pattern: Pattern,
+    pub env_prefix: Option<String>,
+    pub no_std: bool,
+    pub crate_path: Option<syn::Path>,
+    pub vis: Option<syn::Visibility>,
+    pub setter_prefix: Option<String>,
+    pub is_const: bool,
+}
 
-    match &field.ty {
-        Type::Path(
-            TypePath {
-                qself: None,
-                path: Path {
-                    leading_colon: None,
-                    segments,
+impl ContainerFlags {
+    pub fn new(attributes: &[AttributeInfo]) -> Self {
+        let has_flag = |path: &[&str]| find_attribute(attributes, path) == Some(&AttributeValue::Flag);
+
+        let pattern = if has_flag(&["typestate"]) || has_flag(&["const"]) {
+            Pattern::Owned
+        } else {
+            match find_attribute(attributes, &["pattern"]).and_then(AttributeValue::as_str) {
+                Some("owned") => Pattern::Owned,
+                Some("immutable") => Pattern::Immutable,
+                _ => Pattern::Mutable,
+            }
+        };
+
+        Self {
+            default: has_flag(&["default"]),
+            setter_into: has_flag(&["setter", "into"]),
+            try_setter: has_flag(&["try_setter"]),
+            pattern,
+            env_prefix: find_attribute(attributes, &["env_prefix"])
+                .and_then(AttributeValue::as_str)
+                .map(str::to_owned),
+            no_std: has_flag(&["no_std"]),
+            crate_path: find_attribute(attributes, &["crate"])
+                .and_then(AttributeValue::as_str)
+                .and_then(|path| syn::parse_str(path).ok()),
+            vis: find_attribute(attributes, &["vis"])
+                .and_then(AttributeValue::as_str)
+                .and_then(|vis| syn::parse_str(vis).ok()),
+            setter_prefix: find_attribute(attributes, &["setter", "prefix"])
+                .and_then(AttributeValue::as_str)
+                .map(str::to_owned),
+            is_const: has_flag(&["const"]),
+        }
+    }
+
+    pub fn constness(&self) -> proc_macro2::TokenStream {
+        if self.is_const {
+            quote!(const)
+        } else {
+            proc_macro2::TokenStream::new()
+        }
+    }
+
+    pub fn vis(&self) -> proc_macro2::TokenStream {
+        match &self.vis {
+            Some(vis) => quote!(#vis),
+            None => quote!(pub),
+        }
+    }
+
+    pub fn alloc(&self) -> proc_macro2::TokenStream {
+        match &self.crate_path {
+            Some(crate_path) => quote!(#crate_path),
+            None if self.no_std => quote!(::alloc),
+            None => quote!(::std),
+        }
+    }
+
+    pub fn error_trait(&self) -> proc_macro2::TokenStream {
+        if self.no_std {
+            quote!(::core::error::Error)
+        } else {
+            quote!(::std::error::Error)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum Pattern {
+    #[default]
+    Mutable,
+    Owned,
+    Immutable,
+}
+
+impl Pattern {
+    pub fn receiver(self) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => quote!(&mut self),
+            Pattern::Owned => quote!(self),
+            Pattern::Immutable => quote!(&self),
+        }
+    }
+
+    pub fn return_type(self) -> proc_macro2::TokenStream {
+        match self {
+            Pattern::Mutable => quote!(&mut Self),
+            Pattern::Owned | Pattern::Immutable => quote!(Self),
+        }
+    }
+
+    pub fn update(self, update: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let builder = match self {
+            Pattern::Mutable => quote!(let __builder = self;),
+            Pattern::Owned => quote!(let mut __builder = self;),
+            Pattern::Immutable => quote!(let mut __builder = ::core::clone::Clone::clone(self);),
+        };
+
+        quote! {
+            #builder
+            #update
+        }
+    }
+}
+
+#[derive(Debug)]
+enum SpecialField {
+    Option(Type),
+    Collection(Collection),
+}
+
+#[derive(Debug)]
+enum SmartPointer {
+    Box(Type),
+    Rc(Type),
+    Arc(Type),
+    Cow,
+}
+
+#[derive(Debug, Clone)]
+enum Collection {
+    Sequence(Type),
+    Map(Type, Type),
+}
+
+#[derive(Debug)]
+struct FieldInfo {
+    pub ident: Ident,
+    pub member: Member,
+    pub ty: Type,
+    pub special_field: Option<SpecialField>,
+    pub container: ContainerFlags,
+    pub attributes: Vec<AttributeInfo>
+}
+
+impl FieldInfo {
+    pub fn new(index: usize, field: &Field, errors: &mut Vec<syn::Error>) -> Self {
+        let attributes = attributes::parse(&field.attrs, attributes::FIELD_KEYS, errors);
+
+        let (ident, member) = match &field.ident {
+            Some(ident) => (ident.clone(), Member::Named(ident.clone())),
+            None => {
+                let ident = find_attribute(&attributes, &["name"])
+                    .and_then(AttributeValue::as_str)
+                    .map(|name| format_ident!("{}", name))
+                    .unwrap_or_else(|| format_ident!("_{}", index));
+
+                (ident, Member::Unnamed(index.into()))
+            },
+        };
+
+        // `optional` also covers aliases of `Option`, whose inner type is only
+        // reachable through the `IntoIterator` impl of `Option`.
+        let special_field = if find_attribute(&attributes, &["required"]).is_some() {
+            None
+        } else if find_attribute(&attributes, &["optional"]).is_some() {
+            match special_field_info(&field.ty) {
+                Some(SpecialField::Option(inner_type)) => Some(SpecialField::Option(inner_type)),
+                _ => {
+                    let ty = &field.ty;
+                    Some(SpecialField::Option(parse_quote!(<#ty as ::core::iter::IntoIterator>::Item)))
                 },
+            }
+        } else {
+            special_field_info(&field.ty)
+        };
+
+        let field_info = Self {
+            ident,
+            member,
+            ty: field.ty.clone(),
+            special_field,
+            container: ContainerFlags::default(),
+            attributes,
+        };
+        field_info.check(errors);
+
+        field_info
+    }
+
+    fn check(&self, errors: &mut Vec<syn::Error>) {
+        let tag = |name: &str| self.attributes.iter().find(|attr| attr.tag == name).map(|attr| &attr.tag);
+
+        if let (Some(tag), Some(collection_type)) = (tag("each"), self.collection_type()) {
+            if !is_collection(collection_type) {
+                errors.push(syn::Error::new_spanned(
+                    collection_type,
+                    format!("`{}` requires a collection, this type cannot be extended", tag),
+                ));
+            }
+        }
+
+        if self.has_flag(&["setter", "wrap"]) && smart_pointer_info(self.setter_type()).is_none() {
+            errors.push(syn::Error::new_spanned(
+                self.setter_type(),
+                "`wrap` requires a `Box`, `Rc`, `Arc` or `Cow` field",
+            ));
+        }
+
+        if self.has_flag(&["setter", "strip_option"]) && !self.is_optional() {
+            errors.push(syn::Error::new_spanned(&self.ty, "`strip_option` requires an `Option` field"));
+        }
+
+        if let Some(required) = tag("required") {
+            if let Some(conflicting) = tag("optional").or_else(|| tag("default")) {
+                errors.push(syn::Error::new_spanned(
+                    conflicting,
+                    format!("`{}` cannot be combined with `{}`", conflicting, required),
+                ));
+            }
+        }
+
+        if let Some(skip) = tag("skip") {
+            let conflicting = [
+                "each", "sub_builder", "env", "setter", "try_setter", "private", "field_attr", "optional", "required",
+            ]
+                .iter()
+                .find_map(|name| tag(name));
+            if let Some(conflicting) = conflicting {
+                errors.push(syn::Error::new_spanned(
+                    conflicting,
+                    format!("`{}` cannot be combined with `{}`", conflicting, skip),
+                ));
+            }
+        }
+
+        if let Some(skip_cli) = tag("skip_cli") {
+            if let Some(conflicting) = tag("long").or_else(|| tag("short")) {
+                errors.push(syn::Error::new_spanned(
+                    conflicting,
+                    format!("`{}` cannot be combined with `{}`", conflicting, skip_cli),
+                ));
+            }
+        }
+
+        if let Some(sub_builder) = tag("sub_builder") {
+            let conflicting = ["each", "default", "env"].iter().find_map(|name| tag(name));
+            if let Some(conflicting) = conflicting {
+                errors.push(syn::Error::new_spanned(
+                    conflicting,
+                    format!("`{}` cannot be combined with `{}`", conflicting, sub_builder),
+                ));
+            } else if self.is_optional() || self.sub_builder_type().is_none() {
+                errors.push(syn::Error::new_spanned(
+                    &self.ty,
+                    format!("`{}` requires the type of a struct deriving Builder", sub_builder),
+                ));
+            }
+        }
+
+        if let (Some(tag), Some(AttributeValue::Str(expr))) = (tag("default"), self.attribute(&["default"])) {
+            if let Err(error) = syn::parse_str::<syn::Expr>(expr) {
+                errors.push(syn::Error::new_spanned(tag, format!("invalid default expression: {}", error)));
+            }
+        }
+    }
+
+    pub fn attribute(&self, path: &[&str]) -> Option<&AttributeValue> {
+        find_attribute(&self.attributes, path)
+    }
+
+    pub fn each_name(&self) -> Option<Ident> {
+        self.attribute(&["each"])
+            .and_then(AttributeValue::as_str)
+            .map(|value| format_ident!("{}", value))
+    }
+
+    pub fn is_repeated(&self) -> bool {
+        self.each_name().is_some()
+    }
+
+    pub fn collection_type(&self) -> Option<&Type> {
+        if !self.is_repeated() {
+            return None;
+        }
+
+        Some(self.setter_type())
+    }
+
+    pub fn collection(&self) -> Option<Collection> {
+        match special_field_info(self.collection_type()?) {
+            Some(SpecialField::Collection(collection)) => Some(collection),
+            _ => None,
+        }
+    }
+
+    fn collection_mut(&self) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+
+        if self.is_optional() {
+            quote!(__builder.#parameter_name.get_or_insert_with(::core::default::Default::default))
+        } else {
+            quote!(&mut __builder.#parameter_name)
+        }
+    }
+
+    pub fn is_optional(&self) -> bool {
+        matches!(self.special_field, Some(SpecialField::Option(_)))
+    }
+
+    // Optional fields other than `each` collections are stored as
+    // `Option<Option<T>>`, so an explicit `None` is told apart from a field
+    // that was never set.
+    pub fn is_tri_state(&self) -> bool {
+        self.is_optional() && !self.is_repeated()
+    }
+
+    pub fn is_required(&self) -> bool {
+        !self.is_optional()
+            && !self.is_repeated()
+            && !self.is_sub_builder()
+            && !self.is_skipped()
+            && self.default_value().is_none()
+    }
+
+    pub fn is_skipped(&self) -> bool {
+        self.has_flag(&["skip"])
+    }
+
+    pub fn setter_name(&self) -> Ident {
+        if let Some(name) = self.attribute(&["setter", "name"]).and_then(AttributeValue::as_str) {
+            return format_ident!("{}", name);
+        }
+
+        match &self.container.setter_prefix {
+            Some(prefix) => format_ident!("{}{}", prefix, self.ident.unraw()),
+            None => self.ident.clone(),
+        }
+    }
+
+    pub fn vis(&self) -> proc_macro2::TokenStream {
+        if self.has_flag(&["private"]) {
+            proc_macro2::TokenStream::new()
+        } else {
+            self.container.vis()
+        }
+    }
+
+    pub fn is_sub_builder(&self) -> bool {
+        self.has_flag(&["sub_builder"])
+    }
+
+    pub fn sub_builder_type(&self) -> Option<syn::Path> {
+        self.sub_builder_path("Builder", true)
+    }
+
+    fn sub_builder_path(&self, suffix: &str, keep_args: bool) -> Option<syn::Path> {
+        let mut path = match &self.ty {
+            Type::Path(type_path) if type_path.qself.is_none() && self.is_sub_builder() => type_path.path.clone(),
+            _ => return None,
+        };
+
+        let segment = path.segments.last_mut()?;
+        segment.ident = format_ident!("{}{}", segment.ident, suffix);
+        if !keep_args {
+            segment.arguments = syn::PathArguments::None;
+        }
+
+        Some(path)
+    }
+
+    // Code that relies on the nested type deriving a compatible builder is
+    // spanned to `sub_builder`, so that is where errors about it point.
+    pub fn sub_builder_span(&self) -> proc_macro2::Span {
+        self.attributes
+            .iter()
+            .find(|attr| attr.tag == "sub_builder")
+            .map_or_else(proc_macro2::Span::call_site, |attr| attr.tag.span())
+    }
+
+    fn sub_build_local(&self) -> Ident {
+        format_ident!("__sub_{}", self.ident.unraw())
+    }
+
+    pub fn sub_build(&self) -> proc_macro2::TokenStream {
+        let sub_error = match self.sub_builder_path("BuilderError", false) {
+            Some(sub_error) => sub_error,
+            None => return proc_macro2::TokenStream::new(),
+        };
+
+        let sub_builder_type = self.sub_builder_type();
+        let parameter_name = &self.ident;
+        let sub_build_local = self.sub_build_local();
+        let prefix = self.ident.unraw().to_string();
+        let alloc = self.container.alloc();
+
+        let sub_builder = match self.container.pattern {
+            Pattern::Mutable => quote!(let __sub_builder = &mut self.#parameter_name;),
+            Pattern::Owned => quote!(let mut __sub_builder = self.#parameter_name;),
+            Pattern::Immutable => quote!(let mut __sub_builder = ::core::clone::Clone::clone(&self.#parameter_name);),
+        };
+        let build = match self.container.pattern {
+            Pattern::Mutable => quote!(<#sub_builder_type>::__build_by_reference(__sub_builder)),
+            _ => quote!(__sub_builder.__build()),
+        };
+
+        // Only `MissingFields` exists on every builder error, anything else
+        // is reported as a violation of the field.
+        quote_spanned! {self.sub_builder_span()=>
+            let #sub_build_local = {
+                #sub_builder
+                match #build {
+                    ::core::result::Result::Ok(value) => ::core::option::Option::Some(value),
+                    ::core::result::Result::Err(#sub_error::MissingFields(fields)) => {
+                        for field in fields {
+                            missing_fields.push(#alloc::format!("{}.{}", #prefix, field));
+                        }
+                        ::core::option::Option::None
+                    },
+                    #[allow(unreachable_patterns)]
+                    ::core::result::Result::Err(error) => {
+                        violations.push(#alloc::format!("{}: {}", #prefix, error));
+                        ::core::option::Option::None
+                    },
+                }
+            };
+        }
+    }
+
+    pub fn sub_builder_mut(&self) -> proc_macro2::TokenStream {
+        let sub_builder_type = match self.sub_builder_type() {
+            Some(sub_builder_type) => sub_builder_type,
+            None => return proc_macro2::TokenStream::new(),
+        };
+
+        let parameter_name = &self.ident;
+        let function_name = format_ident!("{}_mut", parameter_name.unraw());
+        let vis = self.vis();
+
+        quote! {
+            #vis fn #function_name(&mut self) -> &mut #sub_builder_type {
+                &mut self.#parameter_name
+            }
+        }
+    }
+
+    pub fn accessors(&self) -> proc_macro2::TokenStream {
+        if self.is_skipped() {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let parameter_name = &self.ident;
+        let name = parameter_name.unraw();
+        let getter_name = format_ident!("get_{}", name);
+        let clear_name = format_ident!("clear_{}", name);
+        let is_set_name = format_ident!("is_{}_set", name);
+        let vis = self.vis();
+
+        let (getter_type, getter) = if let Some(sub_builder_type) = self.sub_builder_type() {
+            (quote!(&#sub_builder_type), quote!(&self.#parameter_name))
+        } else if self.is_repeated() && !self.is_optional() {
+            let parameter_type = &self.ty;
+            (quote!(&#parameter_type), quote!(&self.#parameter_name))
+        } else if self.is_tri_state() {
+            let setter_type = self.setter_type();
+            (
+                quote!(::core::option::Option<&#setter_type>),
+                quote!(self.#parameter_name.as_ref().and_then(::core::option::Option::as_ref)),
+            )
+        } else {
+            let setter_type = self.setter_type();
+            (quote!(::core::option::Option<&#setter_type>), quote!(self.#parameter_name.as_ref()))
+        };
+        let is_set = self.is_set_in(&quote!(self));
+        let iterable_bound = if self.is_repeated() && self.collection().is_none() {
+            let collection_type = self.collection_type();
+            quote!(where for<'__a> &'__a #collection_type: ::core::iter::IntoIterator)
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
+        let pattern = self.container.pattern;
+        let receiver = pattern.receiver();
+        let return_type = pattern.return_type();
+        let initial_value = self.initial_value();
+        let update = pattern.update(quote!(__builder.#parameter_name = #initial_value;));
+
+        let len = if self.is_repeated() {
+            let len_name = format_ident!("{}_len", name);
+            let count = |collection: proc_macro2::TokenStream| match self.collection() {
+                Some(_) => quote!((#collection).len()),
+                None => quote!(::core::iter::Iterator::count(::core::iter::IntoIterator::into_iter(#collection))),
+            };
+            let len = if self.is_optional() {
+                let count = count(quote!(collection));
+                quote!(self.#parameter_name.as_ref().map_or(0, |collection| #count))
+            } else {
+                count(quote!(&self.#parameter_name))
+            };
+
+            quote! {
+                #vis fn #len_name(&self) -> usize #iterable_bound {
+                    #len
+                }
+            }
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
+        quote! {
+            #vis fn #getter_name(&self) -> #getter_type {
+                #getter
+            }
+
+            #vis fn #is_set_name(&self) -> bool #iterable_bound {
+                #is_set
+            }
+
+            #vis fn #clear_name(#receiver) -> #return_type {
+                #update
+                __builder
+            }
+
+            #len
+        }
+    }
+
+    pub fn default_value(&self) -> Option<proc_macro2::TokenStream> {
+        match self.attribute(&["default"]) {
+            Some(AttributeValue::Flag) => Some(quote!(::core::default::Default::default())),
+            Some(AttributeValue::Str(expr)) => syn::parse_str::<syn::Expr>(expr)
+                .ok()
+                .map(|expr| quote!(#expr)),
+            // The struct may implement `Drop`, so its fields cannot be moved out.
+            _ if self.takes_struct_default() => {
+                let member = &self.member;
+                Some(quote!(::core::clone::Clone::clone(&__default.#member)))
+            },
+            _ => None,
+        }
+    }
+
+    pub fn takes_struct_default(&self) -> bool {
+        self.container.default && !self.is_repeated() && self.attribute(&["default"]).is_none()
+    }
+
+    pub fn is_set(&self) -> proc_macro2::TokenStream {
+        self.is_set_in(&quote!(self))
+    }
+
+    // Whether `build` has a value for the field, either from the builder or
+    // from its environment variable.
+    pub fn is_provided(&self) -> proc_macro2::TokenStream {
+        let is_set = self.is_set();
+
+        if self.env_var().is_some() {
+            let env_local = self.env_local();
+            quote!((#is_set || #env_local.is_some()))
+        } else {
+            is_set
+        }
+    }
+
+    pub fn is_set_in(&self, builder: &proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+
+        if self.is_skipped() {
+            quote!(false)
+        } else if self.is_sub_builder() {
+            quote!(true)
+        } else if self.is_optional() {
+            quote!(#builder.#parameter_name.is_some())
+        } else if self.is_repeated() && self.collection().is_none() {
+            quote! {
+                ::core::iter::Iterator::next(&mut ::core::iter::IntoIterator::into_iter(&#builder.#parameter_name)).is_some()
+            }
+        } else if self.is_repeated() {
+            quote!(!#builder.#parameter_name.is_empty())
+        } else {
+            quote!(#builder.#parameter_name.is_some())
+        }
+    }
+
+    pub fn field_attributes(&self) -> proc_macro2::TokenStream {
+        let field_attrs = find_metas(&self.attributes, "field_attr");
+
+        quote!(#(#[#field_attrs])*)
+    }
+
+    pub fn field_definition(&self) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+        let parameter_type = &self.ty;
+        let field_attributes = self.field_attributes();
+
+        if self.is_skipped() {
+            proc_macro2::TokenStream::new()
+        } else if let Some(sub_builder_type) = self.sub_builder_type() {
+            quote! {
+                #field_attributes
+                #parameter_name: #sub_builder_type,
+            }
+        } else if self.is_repeated() {
+            quote! {
+                #field_attributes
+                #parameter_name: #parameter_type,
+            }
+        } else {
+            quote! {
+                #field_attributes
+                #parameter_name: ::core::option::Option<#parameter_type>,
+            }
+        }
+    }
+
+    pub fn unbuild(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+
+        if self.is_skipped() {
+            proc_macro2::TokenStream::new()
+        } else if self.is_sub_builder() {
+            quote_spanned!(self.sub_builder_span()=> #parameter_name: ::core::convert::From::from(#value),)
+        } else if self.is_repeated() {
+            quote!(#parameter_name: #value,)
+        } else {
+            quote!(#parameter_name: ::core::option::Option::Some(#value),)
+        }
+    }
+
+    pub fn default_builder(&self) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+
+        if self.is_skipped() {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let initial_value = self.initial_value();
+
+        quote! {
+            #parameter_name: #initial_value,
+        }
+    }
+
+    fn initial_value(&self) -> proc_macro2::TokenStream {
+        if self.is_sub_builder() {
+            let parameter_type = &self.ty;
+            quote_spanned!(self.sub_builder_span()=> <#parameter_type>::builder())
+        } else if self.is_repeated() && !self.is_optional() {
+            quote!(::core::default::Default::default())
+        } else {
+            quote!(::core::option::Option::None)
+        }
+    }
+
+    pub fn has_flag(&self, path: &[&str]) -> bool {
+        self.attribute(path) == Some(&AttributeValue::Flag)
+    }
+
+    pub fn is_setter_into(&self) -> bool {
+        self.container.setter_into || self.has_flag(&["setter", "into"])
+    }
+
+    pub fn is_try_setter(&self) -> bool {
+        self.container.try_setter || self.has_flag(&["try_setter"])
+    }
+
+    pub fn setter_input(&self, name: &Ident, ty: &Type) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        if self.is_setter_into() {
+            (quote!(impl ::core::convert::Into<#ty>), quote!(::core::convert::Into::into(#name)))
+        } else {
+            (quote!(#ty), quote!(#name))
+        }
+    }
+
+    pub fn field_input(&self, name: &Ident) -> (proc_macro2::TokenStream, proc_macro2::TokenStream) {
+        let ty = self.setter_type();
+        let smart_pointer = match smart_pointer_info(ty) {
+            Some(smart_pointer) if self.has_flag(&["setter", "wrap"]) => smart_pointer,
+            _ => return self.setter_input(name, ty),
+        };
+
+        let into_pointer = || (quote!(impl ::core::convert::Into<#ty>), quote!(::core::convert::Into::into(#name)));
+        let alloc = self.container.alloc();
+        let (constructor, inner_type) = match smart_pointer {
+            SmartPointer::Box(inner_type) => (quote!(#alloc::boxed::Box::new), inner_type),
+            SmartPointer::Rc(inner_type) => (quote!(#alloc::rc::Rc::new), inner_type),
+            SmartPointer::Arc(inner_type) => (quote!(#alloc::sync::Arc::new), inner_type),
+            SmartPointer::Cow => return into_pointer(),
+        };
+
+        // Unsized values cannot be moved into the pointer, so trait objects
+        // take any implementor and slices and `str` convert with `Into`.
+        let (input, value) = match &inner_type {
+            Type::TraitObject(trait_object) => {
+                let bounds = &trait_object.bounds;
+                let has_lifetime = bounds.iter().any(|bound| matches!(bound, syn::TypeParamBound::Lifetime(_)));
+                let lifetime = if has_lifetime { None } else { Some(quote!(+ 'static)) };
+                (quote!(impl #bounds #lifetime), quote!(#name))
+            },
+            Type::Slice(_) => return into_pointer(),
+            Type::Path(path) if path.qself.is_none() && path.path.is_ident("str") => return into_pointer(),
+            inner_type => self.setter_input(name, inner_type),
+        };
+
+        (input, quote!({
+            let __wrapped: #ty = #constructor(#value);
+            __wrapped
+        }))
+    }
+
+    pub fn stored_value(&self, value: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+        if self.is_sub_builder() {
+            quote_spanned!(self.sub_builder_span()=> ::core::convert::From::from(#value))
+        } else if self.is_repeated() && !self.is_optional() {
+            value
+        } else if self.is_tri_state() {
+            quote!(::core::option::Option::Some(::core::option::Option::Some(#value)))
+        } else {
+            quote!(::core::option::Option::Some(#value))
+        }
+    }
+
+    pub fn setter(&self) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+        let function_name = self.setter_name();
+        let vis = self.vis();
+
+        if self.is_skipped() || self.each_name().as_ref() == Some(&function_name) {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let (parameter_type, value) = self.field_input(parameter_name);
+        let try_setter = self.try_setter();
+
+        let pattern = self.container.pattern;
+        let receiver = pattern.receiver();
+        let return_type = pattern.return_type();
+        let constness = self.container.constness();
+        let stored = self.stored_value(value);
+        let update = pattern.update(quote!(__builder.#parameter_name = #stored;));
+
+        let none_setter = if self.is_tri_state() {
+            let none_name = format_ident!("{}_none", function_name.unraw());
+            let update = pattern.update(quote! {
+                __builder.#parameter_name = ::core::option::Option::Some(::core::option::Option::None);
+            });
+
+            quote! {
+                #vis #constness fn #none_name(#receiver) -> #return_type {
+                    #update
+                    __builder
+                }
+            }
+        } else {
+            proc_macro2::TokenStream::new()
+        };
+
+        quote! {
+            #vis #constness fn #function_name(#receiver, #parameter_name: #parameter_type) -> #return_type {
+                #update
+                __builder
+            }
+
+            #try_setter
+
+            #none_setter
+        }
+    }
+
+    pub fn try_setter(&self) -> proc_macro2::TokenStream {
+        if !self.is_try_setter() {
+            return proc_macro2::TokenStream::new();
+        }
+
+        let parameter_name = &self.ident;
+        let parameter_type = self.setter_type();
+        let function_name = format_ident!("try_{}", self.setter_name().unraw());
+        let vis = self.vis();
+
+        let pattern = self.container.pattern;
+        let receiver = pattern.receiver();
+        let return_type = pattern.return_type();
+        let stored = self.stored_value(quote!(::core::convert::TryInto::try_into(#parameter_name)?));
+        let update = pattern.update(quote!(__builder.#parameter_name = #stored;));
+
+        quote! {
+            #vis fn #function_name<__T>(#receiver, #parameter_name: __T) -> ::core::result::Result<#return_type, __T::Error>
+            where __T: ::core::convert::TryInto<#parameter_type>
+            {
+                #update
+                ::core::result::Result::Ok(__builder)
+            }
+        }
+    }
+
+    pub fn setter_type(&self) -> &Type {
+        match &self.special_field {
+            Some(SpecialField::Option(inner_type)) => inner_type,
+            _ => &self.ty,
+        }
+    }
+
+    pub fn env_var(&self) -> Option<String> {
+        if self.is_repeated() || self.is_skipped() {
+            return None;
+        }
+
+        if let Some(variable) = self.attribute(&["env"]).and_then(AttributeValue::as_str) {
+            return Some(variable.to_owned());
+        }
+
+        self.container.env_prefix
+            .as_ref()
+            .map(|prefix| format!("{}{}", prefix, self.ident.unraw().to_string().to_uppercase()))
+    }
+
+    fn env_local(&self) -> Ident {
+        format_ident!("__env_{}", self.ident.unraw())
+    }
+
+    pub fn env_lookup(&self, error_name: &Ident) -> proc_macro2::TokenStream {
+        let variable = match self.env_var() {
+            Some(variable) => variable,
+            None => {
+                return match self.attributes.iter().find(|attr| attr.tag == "env") {
+                    Some(attr) if self.is_repeated() => {
+                        syn::Error::new_spanned(&attr.tag, "`env` cannot be used on an `each` collection")
+                            .to_compile_error()
+                    },
+                    _ => proc_macro2::TokenStream::new(),
+                };
             },
-        ) => {
-            let segment = segments.iter().next()?;
-            if segment.ident == "Option" {
-                if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                    let arg = args.args.iter().next()?;
-                    if let GenericArgument::Type(arg_type) = arg {
-                        Some(SpecialField::Option(arg_type.clone()))
-                    } else {
-                        None
+        };
+
+        let env_local = self.env_local();
+        let value_type = self.setter_type();
+        let is_set = self.is_set();
+
+        quote! {
+            let #env_local: ::core::option::Option<#value_type> = if #is_set {
+                ::core::option::Option::None
+            } else {
+                match ::std::env::var(#variable) {
+                    ::core::result::Result::Ok(value) => match value.parse::<#value_type>() {
+                        ::core::result::Result::Ok(value) => ::core::option::Option::Some(value),
+                        ::core::result::Result::Err(error) => {
+                            return ::core::result::Result::Err(#error_name::InvalidEnvVar {
+                                variable: #variable,
+                                error: ::std::string::ToString::to_string(&error),
+                            });
+                        },
+                    },
+                    ::core::result::Result::Err(_) => ::core::option::Option::None,
+                }
+            };
+        }
+    }
+
+    pub fn validation(&self) -> proc_macro2::TokenStream {
+        if self.is_required() {
+            let parameter_name = &self.ident;
+            let env_unset = if self.env_var().is_some() {
+                let env_local = self.env_local();
+                quote!(&& #env_local.is_none())
+            } else {
+                proc_macro2::TokenStream::new()
+            };
+
+            quote! {
+                if self.#parameter_name.is_none() #env_unset {
+                    missing_fields.push(::core::convert::Into::into(::core::stringify!(#parameter_name)));
+                }
+            }
+        } else {
+            proc_macro2::TokenStream::new()
+        }
+    }
+
+    pub fn each(&self) -> proc_macro2::TokenStream {
+        let function_name = match self.each_name() {
+            Some(function_name) if self.is_repeated() => function_name,
+            _ => return proc_macro2::TokenStream::new(),
+        };
+
+        let parameter_name = &self.ident;
+        let parameter_type = self.setter_type();
+        let collection = self.collection_mut();
+        let vis = self.vis();
+
+        let pattern = self.container.pattern;
+        let receiver = pattern.receiver();
+        let return_type = pattern.return_type();
+        let extend = |item: proc_macro2::TokenStream| pattern.update(quote! {
+            ::core::iter::Extend::extend(#collection, ::core::iter::once(#item));
+        });
+
+        let extend_name = format_ident!("{}_extend", parameter_name);
+        let extend_all = |bounds: proc_macro2::TokenStream, items: proc_macro2::TokenStream| {
+            let update = pattern.update(quote! {
+                ::core::iter::Extend::extend(#collection, #items);
+            });
+
+            quote! {
+                #vis fn #extend_name<__I>(#receiver, #parameter_name: __I) -> #return_type
+                where #bounds
+                {
+                    #update
+                    __builder
+                }
+            }
+        };
+
+        let setters = match self.collection() {
+            Some(Collection::Sequence(item_type)) => {
+                let (input_type, item) = self.setter_input(&function_name, &item_type);
+                let update = extend(item);
+                let extend_all = if self.is_setter_into() {
+                    extend_all(
+                        quote!(__I: ::core::iter::IntoIterator, __I::Item: ::core::convert::Into<#item_type>),
+                        quote! {
+                            ::core::iter::Iterator::map(
+                                ::core::iter::IntoIterator::into_iter(#parameter_name),
+                                ::core::convert::Into::into,
+                            )
+                        },
+                    )
+                } else {
+                    extend_all(
+                        quote!(__I: ::core::iter::IntoIterator<Item = #item_type>),
+                        quote!(#parameter_name),
+                    )
+                };
+
+                let try_each = if self.is_try_setter() {
+                    let try_name = format_ident!("try_{}", function_name);
+                    let update = extend(quote!(::core::convert::TryInto::try_into(#function_name)?));
+
+                    quote! {
+                        #vis fn #try_name<__T>(#receiver, #function_name: __T) -> ::core::result::Result<#return_type, __T::Error>
+                        where __T: ::core::convert::TryInto<#item_type>
+                        {
+                            #update
+                            ::core::result::Result::Ok(__builder)
+                        }
                     }
                 } else {
-                    None
+                    proc_macro2::TokenStream::new()
+                };
+
+                quote! {
+                    #vis fn #function_name(#receiver, #function_name: #input_type) -> #return_type {
+                        #update
+                        __builder
+                    }
+
+                    #try_each
+
+                    #extend_all
                 }
-            } else if segment.ident == "Vec" {
-                if let PathArguments::AngleBracketed(args) = &segment.arguments {
-                    let arg = args.args.iter().next()?;
-                    if let GenericArgument::Type(arg_type) = arg {
-                        Some(SpecialField::Vec(arg_type.clone()))
-                    } else {
-                        None
+            },
+            Some(Collection::Map(key_type, value_type)) => {
+                let key_name = format_ident!("key");
+                let value_name = format_ident!("value");
+                let (key_input, key) = self.setter_input(&key_name, &key_type);
+                let (value_input, value) = self.setter_input(&value_name, &value_type);
+                let update = extend(quote!((#key, #value)));
+                let extend_all = extend_all(
+                    quote!(__I: ::core::iter::IntoIterator<Item = (#key_type, #value_type)>),
+                    quote!(#parameter_name),
+                );
+
+                quote! {
+                    #vis fn #function_name(#receiver, #key_name: #key_input, #value_name: #value_input) -> #return_type {
+                        #update
+                        __builder
                     }
-                } else {
-                    None
+
+                    #extend_all
                 }
-            } else {
-                None
+            },
+            None => {
+                let update = extend(quote!(#function_name));
+                let extend_all = extend_all(
+                    quote!(__I: ::core::iter::IntoIterator, #parameter_type: ::core::iter::Extend<__I::Item>),
+                    quote!(#parameter_name),
+                );
+
+                quote! {
+                    #vis fn #function_name<__I>(#receiver, #function_name: __I) -> #return_type
+                    where #parameter_type: ::core::iter::Extend<__I>
+                    {
+                        #update
+                        __builder
+                    }
+
+                    #extend_all
+                }
+            },
+        };
+
+        setters
+    }
+
+    pub fn build(&self) -> proc_macro2::TokenStream {
+        self.build_with(self.container.pattern == Pattern::Owned)
+    }
+
+    pub fn build_with(&self, owned: bool) -> proc_macro2::TokenStream {
+        let parameter_name = &self.ident;
+        let member = &self.member;
+
+        if self.is_skipped() {
+            let default = self.default_value().unwrap_or_else(|| quote!(::core::default::Default::default()));
+            return quote!(#member: #default,);
+        }
+
+        let stored = if owned {
+            quote!(self.#parameter_name)
+        } else {
+            quote!(::core::clone::Clone::clone(&self.#parameter_name))
+        };
+        let stored = match self.env_var() {
+            Some(_) if self.is_tri_state() => {
+                let env_local = self.env_local();
+                quote!(#stored.or(#env_local.map(::core::option::Option::Some)))
+            },
+            Some(_) => {
+                let env_local = self.env_local();
+                quote!(#stored.or(#env_local))
+            },
+            None => stored,
+        };
+
+        let value = match self.default_value() {
+            _ if self.is_sub_builder() => {
+                let sub_build_local = self.sub_build_local();
+                quote!(#sub_build_local.unwrap())
+            },
+            _ if self.is_repeated() => stored,
+            Some(default) => quote! {
+                match #stored {
+                    ::core::option::Option::Some(value) => value,
+                    ::core::option::Option::None => #default,
+                }
+            },
+            None if self.is_optional() => quote!(#stored.unwrap_or(::core::option::Option::None)),
+            None => quote!(#stored.unwrap()),
+        };
+
+        quote! {
+            #member: #value,
+        }
+    }
+}
+
+fn is_collection(ty: &Type) -> bool {
+    const SCALARS: [&str; 17] = [
+        "bool", "char", "str", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize",
+        "u8", "u16", "u32", "u64", "u128", "usize",
+    ];
+
+    match ty {
+        Type::Path(type_path) => !matches!(
+            type_path.path.get_ident(),
+            Some(ident) if SCALARS.iter().any(|scalar| ident == scalar)
+        ),
+        Type::Group(group) => is_collection(&group.elem),
+        Type::Paren(paren) => is_collection(&paren.elem),
+        Type::Tuple(_) | Type::Macro(_) | Type::Verbatim(_) => true,
+        _ => false,
+    }
+}
+
+fn camel_case(ident: &Ident) -> String {
+    ident
+        .to_string()
+        .trim_start_matches("r#")
+        .split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn snake_case(ident: &Ident) -> String {
+    let mut snake_case = String::new();
+
+    for (index, ch) in ident.to_string().trim_start_matches("r#").char_indices() {
+        if ch.is_uppercase() {
+            if index > 0 {
+                snake_case.push('_');
             }
+            snake_case.extend(ch.to_lowercase());
+        } else {
+            snake_case.push(ch);
+        }
+    }
+
+    snake_case
+}
+
+fn std_type_args(ty: &Type) -> Option<(String, Vec<Type>)> {
+    use syn::{Path, TypePath, PathArguments, GenericArgument};
+
+    let (leading_colon, segments) = match ty {
+        Type::Path(
+            TypePath {
+                qself: None,
+                path: Path {
+                    leading_colon,
+                    segments,
+                },
+            },
+        ) => (leading_colon, segments),
+        _ => return None,
+    };
+
+    // Besides the bare names from the prelude or a `use`, the types are
+    // recognised by their paths through `std`, `core` or `alloc`.
+    let segment = segments.last()?;
+    let qualified = match segments.first() {
+        Some(root) if segments.len() > 1 => {
+            ["std", "core", "alloc"].iter().any(|name| root.ident == name)
+                && segments.iter().take(segments.len() - 1).all(|module| module.arguments.is_empty())
         },
+        _ => leading_colon.is_none(),
+    };
+    if !qualified {
+        return None;
+    }
+
+    let args: Vec<Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args.args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(arg_type) => Some(arg_type.clone()),
+                _ => None,
+            })
+            .collect(),
+        _ => return None,
+    };
+
+    Some((segment.ident.to_string(), args))
+}
+
+fn special_field_info(ty: &Type) -> Option<SpecialField> {
+    let (ident, args) = std_type_args(ty)?;
+    match (ident.as_str(), args.as_slice()) {
+        ("Option", [inner_type]) => Some(SpecialField::Option(inner_type.clone())),
+        ("Vec", [item_type])
+        | ("VecDeque", [item_type])
+        | ("LinkedList", [item_type])
+        | ("BinaryHeap", [item_type])
+        | ("HashSet", [item_type, ..])
+        | ("BTreeSet", [item_type]) => {
+            Some(SpecialField::Collection(Collection::Sequence(item_type.clone())))
+        },
+        ("HashMap", [key_type, value_type, ..]) | ("BTreeMap", [key_type, value_type]) => {
+            Some(SpecialField::Collection(Collection::Map(key_type.clone(), value_type.clone())))
+        },
+        _ => None,
+    }
+}
+
+fn smart_pointer_info(ty: &Type) -> Option<SmartPointer> {
+    let (ident, args) = std_type_args(ty)?;
+    match (ident.as_str(), args.as_slice()) {
+        ("Box", [inner_type]) => Some(SmartPointer::Box(inner_type.clone())),
+        ("Rc", [inner_type]) => Some(SmartPointer::Rc(inner_type.clone())),
+        ("Arc", [inner_type]) => Some(SmartPointer::Arc(inner_type.clone())),
+        ("Cow", [_]) => Some(SmartPointer::Cow),
         _ => None,
     }
 }
@@ -0,0 +1,120 @@
+//! `merge`, which layers one builder on top of another. It is generated for
+//! `#[builder(merge)]` or when any field has a `merge` strategy, so existing
+//! fields named `merge` keep working. Every field that is set in `other` is
+//! taken over according to its `merge` strategy:
+//!
+//! - `replace` (the default) overwrites the value in `self`,
+//! - `append` extends an `each` collection in `self` with the items of `other`,
+//! - `keep_first` only takes the value if `self` has not set it yet.
+//!
+//! Sub-builders are merged recursively, field by field, so their builders
+//! need `merge` as well.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::Ident;
+
+use crate::{FieldInfo, StructInfo};
+
+pub fn expand(struct_info: &StructInfo) -> syn::Result<TokenStream> {
+    if !is_enabled(struct_info) {
+        return Ok(TokenStream::new());
+    }
+
+    let merged_fields = struct_info.fields
+        .iter()
+        .filter(|field| !field.is_skipped())
+        .map(merge_field)
+        .collect::<syn::Result<Vec<_>>>()?;
+
+    // Telling whether an arbitrary `Extend` collection has been set needs
+    // `&T: IntoIterator`. The higher-ranked bound keeps the builder compiling
+    // for collections without it, only `merge` is then unavailable.
+    let iterable_bounds = struct_info.fields
+        .iter()
+        .filter(|field| field.is_repeated() && !field.is_optional() && field.collection().is_none())
+        .filter(|field| !matches!(strategy(field), Some((_, Some("append")))))
+        .map(|field| {
+            let collection_type = field.collection_type();
+            quote!(for<'__a> &'__a #collection_type: ::core::iter::IntoIterator)
+        })
+        .collect::<Vec<_>>();
+    let where_clause = if iterable_bounds.is_empty() {
+        TokenStream::new()
+    } else {
+        quote!(where #(#iterable_bounds),*)
+    };
+
+    let vis = struct_info.container().vis();
+
+    Ok(quote! {
+        #vis fn merge(&mut self, other: Self) -> &mut Self
+        #where_clause
+        {
+            #(#merged_fields)*
+            self
+        }
+    })
+}
+
+pub fn is_enabled(struct_info: &StructInfo) -> bool {
+    struct_info.has_flag("merge") || struct_info.fields.iter().any(|field| strategy(field).is_some())
+}
+
+fn strategy(field: &FieldInfo) -> Option<(&Ident, Option<&str>)> {
+    field.attributes
+        .iter()
+        .find(|attr| attr.tag == "merge")
+        .map(|attr| (&attr.tag, attr.value.as_str()))
+}
+
+fn merge_field(field: &FieldInfo) -> syn::Result<TokenStream> {
+    let parameter_name = &field.ident;
+    let other_is_set = field.is_set_in(&quote!(other));
+    let self_is_set = field.is_set();
+
+    let strategy = match strategy(field) {
+        Some((_, Some(strategy))) => strategy,
+        _ => "replace",
+    };
+
+    let merged = match strategy {
+        "append" if !field.is_repeated() => {
+            let (tag, _) = self::strategy(field).unwrap();
+            return Err(syn::Error::new_spanned(
+                tag,
+                "`merge = \"append\"` requires an `each` collection",
+            ));
+        },
+        "append" if field.is_optional() => quote! {
+            if let ::core::option::Option::Some(items) = other.#parameter_name {
+                ::core::iter::Extend::extend(
+                    self.#parameter_name.get_or_insert_with(::core::default::Default::default),
+                    items,
+                );
+            }
+        },
+        "append" => quote! {
+            ::core::iter::Extend::extend(&mut self.#parameter_name, other.#parameter_name);
+        },
+        "keep_first" if field.is_sub_builder() => quote_spanned! {field.sub_builder_span()=>
+            let first = ::core::mem::replace(&mut self.#parameter_name, other.#parameter_name);
+            self.#parameter_name.merge(first);
+        },
+        _ if field.is_sub_builder() => quote_spanned! {field.sub_builder_span()=>
+            self.#parameter_name.merge(other.#parameter_name);
+        },
+        "keep_first" => quote! {
+            if #other_is_set && !#self_is_set {
+                self.#parameter_name = other.#parameter_name;
+            }
+        },
+        _ => quote! {
+            if #other_is_set {
+                self.#parameter_name = other.#parameter_name;
+            }
+        },
+    };
+
+    Ok(merged)
+}
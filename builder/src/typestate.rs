@@ -0,0 +1,282 @@
+//! Expansion for `#[builder(typestate)]`.
+//!
+//! Every required field gets its own type parameter on the builder. The
+//! parameter is `()` while the field is unset and `(T,)` once a value has been
+//! given, so `build` only exists on a builder whose required fields are all
+//! set and a missing field is reported by the compiler instead of at runtime.
+
+use proc_macro2::TokenStream;
+use quote::{quote, format_ident};
+use syn::ext::IdentExt;
+use syn::{GenericParam, Generics, Ident, TypeParam, parse_quote};
+
+use crate::{data_from_fields, FieldInfo, StructInfo};
+
+pub fn expand(struct_info: &StructInfo) -> TokenStream {
+    let unsupported = if crate::validate::has_checks(struct_info) {
+        Some("typestate builders cannot be combined with build_fn(validate), requires, conflicts_with or group")
+    } else if crate::merge::is_enabled(struct_info) {
+        Some("typestate builders cannot be merged, remove `merge`")
+    } else if struct_info.fields.iter().any(FieldInfo::is_sub_builder) {
+        Some("typestate builders cannot contain sub-builders, remove `sub_builder` from the fields")
+    } else if struct_info.fields.iter().any(|field| field.env_var().is_some()) {
+        Some("typestate builders cannot read fields from the environment, remove `env` and `env_prefix`")
+    } else if struct_info.has_flag("const") {
+        Some("typestate builders cannot be const, remove `const`")
+    } else if struct_info.has_flag("cli") {
+        Some("typestate builders cannot parse command-line arguments, remove `cli`")
+    } else {
+        None
+    };
+
+    if let Some(message) = unsupported {
+        let tag = struct_info.attributes
+            .iter()
+            .find(|attr| attr.tag == "typestate")
+            .map(|attr| &attr.tag);
+
+        return syn::Error::new_spanned(tag, message).to_compile_error();
+    }
+
+    let struct_name = &struct_info.ident;
+    let builder_name = struct_info.builder_ident();
+    let builder_fn_name = struct_info.builder_fn_ident();
+    let build_fn_name = struct_info.build_fn_ident();
+    let vis = struct_info.container().vis();
+    let constructor = struct_info.constructor();
+
+    let generics = &struct_info.generics;
+    let struct_args = struct_info.generic_args();
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let required: Vec<&FieldInfo> = struct_info.fields
+        .iter()
+        .filter(|field| field.is_required())
+        .collect();
+    let params: Vec<Ident> = (0..required.len()).map(state_param).collect();
+    let set_types: Vec<TokenStream> = required
+        .iter()
+        .map(|field| {
+            let ty = &field.ty;
+            quote!((#ty,))
+        })
+        .collect();
+
+    let mut required_params = params.iter();
+    let field_definitions: Vec<TokenStream> = struct_info.fields
+        .iter()
+        .map(|field| {
+            if field.is_required() {
+                let parameter_name = &field.ident;
+                let param = required_params.next().unwrap();
+                let field_attributes = field.field_attributes();
+                quote! {
+                    #field_attributes
+                    #parameter_name: #param,
+                }
+            } else {
+                field.field_definition()
+            }
+        })
+        .collect();
+    let default_builders: Vec<TokenStream> = struct_info.fields
+        .iter()
+        .map(|field| {
+            if field.is_required() {
+                let parameter_name = &field.ident;
+                quote!(#parameter_name: (),)
+            } else {
+                field.default_builder()
+            }
+        })
+        .collect();
+
+    let declared_generics = with_state_params(generics, params.iter().map(|param| parse_quote!(#param = ())));
+    let state_generics = with_state_params(generics, params.iter().map(|param| parse_quote!(#param)));
+    let (state_impl_generics, _, _) = state_generics.split_for_impl();
+
+    let required_setters = required
+        .iter()
+        .enumerate()
+        .map(|(index, field)| required_setter(struct_info, &params, index, field));
+
+    let optional_fields: Vec<&FieldInfo> = struct_info.fields
+        .iter()
+        .filter(|field| !field.is_required())
+        .collect();
+    let optional_setters = optional_fields
+        .iter()
+        .map(|field| field.setter());
+    let each_builders = optional_fields
+        .iter()
+        .map(|field| field.each());
+    let accessors = optional_fields
+        .iter()
+        .map(|field| field.accessors());
+
+    let field_builders = data_from_fields(&struct_info.fields, |field| {
+        let parameter_name = &field.ident;
+        let member = &field.member;
+        if field.is_required() {
+            quote!(#member: self.#parameter_name.0,)
+        } else {
+            field.build()
+        }
+    });
+    let struct_default = struct_info.struct_default();
+    let default_bounds = struct_info.default_bounds();
+
+    let from_struct = if struct_info.variant.is_none() {
+        let moved_fields = struct_info.fields.iter().map(|field| {
+            let parameter_name = &field.ident;
+            let member = &field.member;
+            if field.is_required() {
+                quote!(#parameter_name: (value.#member,),)
+            } else {
+                field.unbuild(quote!(value.#member))
+            }
+        });
+
+        quote! {
+            impl #impl_generics ::core::convert::From<#struct_name #ty_generics> for #builder_name<#(#struct_args,)* #(#set_types),*> #where_clause {
+                fn from(value: #struct_name #ty_generics) -> Self {
+                    #builder_name {
+                        #(#moved_fields)*
+                        __marker: ::core::marker::PhantomData,
+                    }
+                }
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    let builder_attributes = struct_info.builder_attributes(false);
+
+    quote! {
+        #builder_attributes
+        #vis struct #builder_name #declared_generics #where_clause {
+            #(#field_definitions)*
+            __marker: ::core::marker::PhantomData<fn() -> #struct_name #ty_generics>,
+        }
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            #vis fn #builder_fn_name() -> #builder_name #ty_generics {
+                #builder_name {
+                    #(#default_builders)*
+                    __marker: ::core::marker::PhantomData,
+                }
+            }
+        }
+
+        #(#required_setters)*
+
+        impl #state_impl_generics #builder_name<#(#struct_args,)* #(#params),*> #where_clause {
+            #(#optional_setters)*
+
+            #(#each_builders)*
+
+            #(#accessors)*
+        }
+
+        impl #impl_generics #builder_name<#(#struct_args,)* #(#set_types),*> #where_clause {
+            #vis fn #build_fn_name(self) -> #struct_name #ty_generics
+            #default_bounds
+            {
+                #struct_default
+
+                #constructor {
+                    #(#field_builders)*
+                }
+            }
+        }
+
+        #from_struct
+    }
+}
+
+fn required_setter(
+    struct_info: &StructInfo,
+    params: &[Ident],
+    index: usize,
+    field: &FieldInfo,
+) -> TokenStream {
+    let builder_name = struct_info.builder_ident();
+    let parameter_name = &field.ident;
+    let parameter_type = &field.ty;
+    let function_name = field.setter_name();
+    let vis = field.vis();
+
+    let struct_args = struct_info.generic_args();
+    let other_params = params
+        .iter()
+        .enumerate()
+        .filter(|(other, _)| *other != index)
+        .map(|(_, param)| parse_quote!(#param));
+    let generics = with_state_params(&struct_info.generics, other_params);
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let unset_args = params
+        .iter()
+        .enumerate()
+        .map(|(other, param)| if other == index { quote!(()) } else { quote!(#param) });
+    let set_args = params
+        .iter()
+        .enumerate()
+        .map(|(other, param)| if other == index { quote!((#parameter_type,)) } else { quote!(#param) });
+
+    let (input_type, value) = field.field_input(parameter_name);
+    let moved_fields = struct_info.fields
+        .iter()
+        .filter(|other| !other.is_skipped())
+        .map(|other| {
+            let other_name = &other.ident;
+            if other_name == parameter_name {
+                quote!(#other_name: (#value,),)
+            } else {
+                quote!(#other_name: self.#other_name,)
+            }
+        });
+
+    let set_builder = quote!(#builder_name<#(#struct_args,)* #(#set_args),*>);
+    let try_setter = if field.is_try_setter() {
+        let try_name = format_ident!("try_{}", function_name.unraw());
+
+        quote! {
+            #vis fn #try_name<__T>(self, #parameter_name: __T) -> ::core::result::Result<#set_builder, __T::Error>
+            where __T: ::core::convert::TryInto<#parameter_type>
+            {
+                let #parameter_name: #parameter_type = ::core::convert::TryInto::try_into(#parameter_name)?;
+                ::core::result::Result::Ok(self.#function_name(#parameter_name))
+            }
+        }
+    } else {
+        TokenStream::new()
+    };
+
+    quote! {
+        impl #impl_generics #builder_name<#(#struct_args,)* #(#unset_args),*> #where_clause {
+            #vis fn #function_name(self, #parameter_name: #input_type) -> #set_builder {
+                #builder_name {
+                    #(#moved_fields)*
+                    __marker: self.__marker,
+                }
+            }
+
+            #try_setter
+        }
+    }
+}
+
+fn with_state_params<I>(generics: &Generics, params: I) -> Generics
+where I: IntoIterator<Item = TypeParam>
+{
+    let mut generics = generics.clone();
+    generics.params.extend(params.into_iter().map(GenericParam::Type));
+    generics
+}
+
+// Named by position, field names like `_a` and `a` would give the same
+// camel-cased name.
+fn state_param(index: usize) -> Ident {
+    format_ident!("__S{}", index)
+}
@@ -0,0 +1,174 @@
+//! Checks run by `build`: the declarative `requires`, `conflicts_with` and
+//! `group` field attributes and the `build_fn(validate = "...")` hook. They
+//! run even when required fields are missing, and every violation is pushed
+//! onto `violations` so that all of them are reported together with the
+//! missing fields. A field counts as set when it has a value from its
+//! environment variable.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Ident;
+
+use crate::attributes::find_attribute;
+use crate::{AttributeValue, FieldInfo, StructInfo};
+
+const GROUP_CONSTRAINTS: [&str; 3] = ["exactly_one", "at_least_one", "at_most_one"];
+
+pub fn has_checks(struct_info: &StructInfo) -> bool {
+    struct_info.attribute(&["build_fn", "validate"]).is_some()
+        || struct_info.fields.iter().any(|field| {
+            field.attributes
+                .iter()
+                .any(|attr| attr.tag == "requires" || attr.tag == "conflicts_with" || attr.tag == "group")
+        })
+}
+
+pub fn expand(struct_info: &StructInfo) -> syn::Result<TokenStream> {
+    let alloc = struct_info.container().alloc();
+    let mut checks = Vec::new();
+
+    for field in &struct_info.fields {
+        let parameter_name = &field.ident;
+        let is_set = field.is_provided();
+
+        for (tag, other_name) in field_references(field, "requires") {
+            let other = find_field(struct_info, tag, &other_name)?;
+            let other_is_set = other.is_provided();
+            let message = format!("`{}` requires `{}` to be set", parameter_name, other.ident);
+
+            checks.push(quote! {
+                if #is_set && !#other_is_set {
+                    violations.push(#alloc::string::String::from(#message));
+                }
+            });
+        }
+
+        for (tag, other_name) in field_references(field, "conflicts_with") {
+            let other = find_field(struct_info, tag, &other_name)?;
+            let other_is_set = other.is_provided();
+            let message = format!("`{}` conflicts with `{}`", parameter_name, other.ident);
+
+            checks.push(quote! {
+                if #is_set && #other_is_set {
+                    violations.push(#alloc::string::String::from(#message));
+                }
+            });
+        }
+    }
+
+    for group in groups(struct_info)? {
+        checks.push(group.check(&alloc));
+    }
+
+    if let Some(validate) = struct_info.attribute(&["build_fn", "validate"]).and_then(AttributeValue::as_str) {
+        let validate: syn::Path = syn::parse_str(validate).map_err(|error| {
+            let tag = &struct_info.attributes
+                .iter()
+                .find(|attr| find_attribute(std::slice::from_ref(attr), &["build_fn", "validate"]).is_some())
+                .unwrap()
+                .tag;
+            syn::Error::new_spanned(tag, format!("invalid validate function: {}", error))
+        })?;
+
+        checks.push(quote! {
+            if let ::core::result::Result::Err(error) = #validate(&self) {
+                violations.push(#alloc::string::ToString::to_string(&error));
+            }
+        });
+    }
+
+    Ok(quote! {
+        #(#checks)*
+    })
+}
+
+fn field_references<'a>(field: &'a FieldInfo, tag: &'a str) -> impl Iterator<Item = (&'a Ident, String)> {
+    field.attributes
+        .iter()
+        .filter(move |attr| attr.tag == tag)
+        .filter_map(|attr| Some((&attr.tag, attr.value.as_str()?.to_owned())))
+}
+
+fn find_field<'a>(struct_info: &'a StructInfo, tag: &Ident, name: &str) -> syn::Result<&'a FieldInfo> {
+    struct_info.fields
+        .iter()
+        .find(|field| field.ident == name)
+        .ok_or_else(|| syn::Error::new_spanned(tag, format!("no field named `{}`", name)))
+}
+
+struct Group<'a> {
+    name: String,
+    tag: &'a Ident,
+    fields: Vec<&'a FieldInfo>,
+    at_least_one: bool,
+    at_most_one: bool,
+}
+
+fn groups(struct_info: &StructInfo) -> syn::Result<Vec<Group<'_>>> {
+    let mut groups: Vec<Group> = Vec::new();
+
+    for field in &struct_info.fields {
+        for (tag, name) in field_references(field, "group") {
+            let index = match groups.iter().position(|group| group.name == name) {
+                Some(index) => index,
+                None => {
+                    groups.push(Group {
+                        name,
+                        tag,
+                        fields: Vec::new(),
+                        at_least_one: false,
+                        at_most_one: false,
+                    });
+                    groups.len() - 1
+                },
+            };
+
+            let group = &mut groups[index];
+            group.fields.push(field);
+            group.at_least_one |= field.has_flag(&["exactly_one"]) || field.has_flag(&["at_least_one"]);
+            group.at_most_one |= field.has_flag(&["exactly_one"]) || field.has_flag(&["at_most_one"]);
+        }
+    }
+
+    for group in &groups {
+        if !group.at_least_one && !group.at_most_one {
+            return Err(syn::Error::new_spanned(
+                group.tag,
+                format!(
+                    "group `{}` needs one of `{}`",
+                    group.name,
+                    GROUP_CONSTRAINTS.join("`, `"),
+                ),
+            ));
+        }
+    }
+
+    Ok(groups)
+}
+
+impl Group<'_> {
+    fn check(&self, alloc: &TokenStream) -> TokenStream {
+        let is_set = self.fields.iter().map(|field| field.is_provided());
+        let names = self.fields
+            .iter()
+            .map(|field| format!("`{}`", field.ident))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let (condition, message) = match (self.at_least_one, self.at_most_one) {
+            (true, true) => (quote!(set_count != 1), format!("exactly one of {} must be set", names)),
+            (true, false) => (quote!(set_count == 0), format!("at least one of {} must be set", names)),
+            _ => (quote!(set_count > 1), format!("at most one of {} may be set", names)),
+        };
+
+        quote! {
+            {
+                let mut set_count = 0;
+                #(if #is_set { set_count += 1; })*
+                if #condition {
+                    violations.push(#alloc::string::String::from(#message));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,53 @@
+// With #[builder(typestate)] every required field is tracked in the type of
+// the builder, so `build` is only callable once all of them have been set and
+// returns the struct directly instead of a Result.
+//
+// Optional fields and `each` collections can be set at any point and in any
+// order, required setters consume the builder and can only be called once.
+// Fields whose names only differ in underscores, like `_x` and `x`, are
+// tracked separately.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Offset {
+    _x: i32,
+    x: i32,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .env(vec![])
+        .executable("cargo".to_owned())
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert!(command.current_dir.is_none());
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .env(vec!["RUST_LOG=debug".to_owned()])
+        .build();
+
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert!(command.args.is_empty());
+
+    let offset = Offset::builder().x(2)._x(1).build();
+    assert_eq!((offset._x, offset.x), (1, 2));
+}
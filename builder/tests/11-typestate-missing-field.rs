@@ -0,0 +1,19 @@
+// In typestate mode forgetting a required field is a compile error rather
+// than a runtime error from `build`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let _command = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("..".to_owned())
+        .build();
+}
@@ -0,0 +1,58 @@
+// The generated build method reports missing fields through a typed error,
+// CommandBuilderError, instead of a boxed string. Callers can match on it to
+// find out exactly which fields were not provided.
+//
+// #[builder(build_fn(error = "..."))] changes the error type returned from
+// build to any type implementing From<{Name}BuilderError>.
+//
+// A builder that only checks for missing fields has no other error variants,
+// so a match on MissingFields alone is exhaustive. Variants for other
+// failures are only added by the options that can cause them.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Incomplete(Vec<&'static str>),
+}
+
+impl From<ServerBuilderError> for ConfigError {
+    fn from(error: ServerBuilderError) -> Self {
+        match error {
+            ServerBuilderError::MissingFields(fields) => ConfigError::Incomplete(fields),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(build_fn(error = "ConfigError"))]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+fn main() {
+    let error = match Command::builder().current_dir("..".to_owned()).build() {
+        Err(error) => error,
+        Ok(command) => panic!("unexpected command {}", command.executable),
+    };
+    assert_eq!(error, CommandBuilderError::MissingFields(vec!["executable", "args"]));
+    assert_eq!(error.to_string(), "missing fields: executable, args");
+
+    let boxed: Box<dyn std::error::Error> = Box::new(error);
+    assert!(boxed.source().is_none());
+
+    match Server::builder().host("localhost".to_owned()).build() {
+        Err(ConfigError::Incomplete(fields)) => assert_eq!(fields, ["port"]),
+        Ok(server) => panic!("unexpected server {}:{}", server.host, server.port),
+    }
+}
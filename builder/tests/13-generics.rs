@@ -0,0 +1,69 @@
+// Generic parameters of the input struct, including lifetimes, const
+// generics and where-clauses, are carried over to the builder, its `builder`
+// constructor and its `build` method.
+
+use derive_builder::Builder;
+use std::fmt::Debug;
+
+pub trait Codec {
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Clone, Debug)]
+pub struct Json;
+
+impl Codec for Json {
+    fn name(&self) -> &'static str {
+        "json"
+    }
+}
+
+#[derive(Builder)]
+pub struct Request<'a, T: Codec, const N: usize>
+where
+    T: Debug,
+{
+    path: &'a str,
+    codec: T,
+    header: [u8; N],
+    #[builder(each = "param")]
+    params: Vec<(&'a str, &'a str)>,
+    timeout: Option<u32>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Response<'a, T: Codec> {
+    body: &'a [u8],
+    codec: T,
+    status: Option<u16>,
+}
+
+fn main() {
+    let path = String::from("/users");
+
+    let request: Request<Json, 2> = Request::builder()
+        .path(&path)
+        .codec(Json)
+        .header([1, 2])
+        .param(("page", "1"))
+        .build()
+        .unwrap();
+
+    assert_eq!(request.path, "/users");
+    assert_eq!(request.codec.name(), "json");
+    assert_eq!(request.header, [1, 2]);
+    assert_eq!(request.params, vec![("page", "1")]);
+    assert!(request.timeout.is_none());
+
+    let body = vec![b'{', b'}'];
+    let response = Response::builder()
+        .status(200)
+        .codec(Json)
+        .body(&body)
+        .build();
+
+    assert_eq!(response.body, b"{}");
+    assert_eq!(response.codec.name(), "json");
+    assert_eq!(response.status, Some(200));
+}
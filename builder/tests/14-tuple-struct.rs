@@ -0,0 +1,37 @@
+// Tuple structs get positional setters named `_0`, `_1`, ... unless a field
+// is given a name through #[builder(name = "...")].
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Point(i32, i32, #[builder(name = "label")] Option<String>);
+
+#[derive(Builder)]
+pub struct Command(
+    #[builder(name = "executable")] String,
+    #[builder(name = "args", each = "arg")] Vec<String>,
+);
+
+fn main() {
+    let point = Point::builder()._0(1)._1(2).build().unwrap();
+    assert_eq!((point.0, point.1), (1, 2));
+    assert!(point.2.is_none());
+
+    let point = Point::builder()._1(4)._0(3).label("origin".to_owned()).build().unwrap();
+    assert_eq!((point.0, point.1), (3, 4));
+    assert_eq!(point.2.as_deref(), Some("origin"));
+
+    let error = match Point::builder()._1(4).build() {
+        Err(error) => error,
+        Ok(point) => panic!("unexpected point {}", point.0),
+    };
+    assert_eq!(error, PointBuilderError::MissingFields(vec!["_0"]));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(command.0, "cargo");
+    assert_eq!(command.1, vec!["build"]);
+}
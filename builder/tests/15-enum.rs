@@ -0,0 +1,57 @@
+// Deriving Builder on an enum generates one builder per variant with fields,
+// created through `<variant in snake case>_builder()` and named
+// `{Enum}{Variant}Builder`. Unit variants do not get a builder. A variant can
+// name its builder with #[builder(name = "...")].
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        radius: f64,
+        label: Option<String>,
+    },
+    Polygon {
+        #[builder(each = "point")]
+        points: Vec<(f64, f64)>,
+    },
+    #[builder(name = "RectangleBuilder")]
+    Rect(f64, f64),
+    Empty,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub enum Message {
+    Text { body: String },
+    HttpRequest { path: String, method: Option<String> },
+}
+
+fn main() {
+    let circle = Shape::circle_builder().radius(1.5).build().unwrap();
+    assert_eq!(circle, Shape::Circle { radius: 1.5, label: None });
+
+    let polygon = Shape::polygon_builder()
+        .point((0.0, 0.0))
+        .point((1.0, 0.0))
+        .point((0.0, 1.0))
+        .build()
+        .unwrap();
+    assert_eq!(polygon, Shape::Polygon { points: vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)] });
+
+    let rect = Shape::rect_builder()._0(2.0)._1(3.0).build().unwrap();
+    assert_eq!(rect, Shape::Rect(2.0, 3.0));
+
+    let error = Shape::circle_builder().label("c".to_owned()).build().unwrap_err();
+    assert_eq!(error, ShapeCircleBuilderError::MissingFields(vec!["radius"]));
+
+    let _: RectangleBuilder = Shape::rect_builder();
+    let error = Shape::rect_builder()._0(2.0).build().unwrap_err();
+    assert_eq!(error, RectangleBuilderError::MissingFields(vec!["_1"]));
+
+    let request = Message::http_request_builder().path("/".to_owned()).build();
+    assert_eq!(request, Message::HttpRequest { path: "/".to_owned(), method: None });
+
+    let text = Message::text_builder().body("hi".to_owned()).build();
+    assert_eq!(text, Message::Text { body: "hi".to_owned() });
+}
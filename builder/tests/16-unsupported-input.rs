@@ -0,0 +1,24 @@
+// Builder cannot be derived for unit structs or unions; the macro reports a
+// compile error pointing at the offending item instead of generating an
+// unusable builder. Likewise a builder name on an enum would be shared by the
+// builders of all its variants, so it has to be given per variant.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Marker;
+
+#[derive(Builder)]
+pub union Bits {
+    int: u32,
+    float: f32,
+}
+
+#[derive(Builder)]
+#[builder(name = "ShapeOptions")]
+pub enum Shape {
+    Circle { radius: f64 },
+    Square { side: f64 },
+}
+
+fn main() {}
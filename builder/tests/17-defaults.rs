@@ -0,0 +1,107 @@
+// Fields marked #[builder(default)] fall back to Default::default() when they
+// are not set, and #[builder(default = "...")] evaluates the given expression
+// at build time instead. Neither kind of field is reported as missing.
+//
+// A container-level #[builder(default)] takes every unset field from the
+// struct's own Default implementation. The values are cloned out of it, so
+// the struct may implement Drop.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(default = "vec![\"PATH=/usr/bin\".to_owned()]")]
+    env: Vec<String>,
+    #[builder(default = "Some(String::from(\"/tmp\"))")]
+    current_dir: Option<String>,
+    #[builder(default = "30 * 1000")]
+    timeout_ms: u64,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(default)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(default = "4")]
+    workers: usize,
+    tls: Option<bool>,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server {
+            host: "localhost".to_owned(),
+            port: 8080,
+            workers: 1,
+            tls: Some(false),
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct TempDir<T> {
+    path: String,
+    mode: T,
+}
+
+impl<T: Default> Default for TempDir<T> {
+    fn default() -> Self {
+        TempDir { path: "/tmp/build".to_owned(), mode: T::default() }
+    }
+}
+
+impl<T> Drop for TempDir<T> {
+    fn drop(&mut self) {}
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+    #[builder(default = "3")]
+    retries: u32,
+}
+
+fn main() {
+    let command = Command::builder().executable("cargo".to_owned()).build().unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.env, vec!["PATH=/usr/bin"]);
+    assert_eq!(command.current_dir.as_deref(), Some("/tmp"));
+    assert_eq!(command.timeout_ms, 30_000);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .args(vec!["build".to_owned()])
+        .current_dir("..".to_owned())
+        .timeout_ms(5)
+        .build()
+        .unwrap();
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout_ms, 5);
+
+    let error = Command::builder().build().unwrap_err();
+    assert_eq!(error, CommandBuilderError::MissingFields(vec!["executable"]));
+
+    let server = Server::builder().port(9000).build().unwrap();
+    assert_eq!(
+        server,
+        Server {
+            host: "localhost".to_owned(),
+            port: 9000,
+            workers: 4,
+            tls: Some(false),
+        },
+    );
+
+    let dir = TempDir::builder().mode(0o700).build().unwrap();
+    assert_eq!((dir.path.as_str(), dir.mode), ("/tmp/build", 0o700));
+
+    let job = Job::builder().name("backup".to_owned()).build();
+    assert_eq!((job.name.as_str(), job.retries), ("backup", 3));
+}
@@ -0,0 +1,86 @@
+// #[builder(setter(into))] makes a setter accept anything convertible into
+// the field type, so callers can pass a &str for a String field. It can be
+// put on individual fields or on the struct to apply to every setter.
+//
+// #[builder(try_setter)] additionally generates a fallible `try_<name>`
+// setter accepting any type that implements TryInto for the field type.
+//
+// Both apply to `each` setters for the element type as well.
+
+use derive_builder::Builder;
+use std::convert::TryFrom;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(into))]
+    current_dir: Option<String>,
+    #[builder(try_setter)]
+    niceness: i8,
+    #[builder(each = "port", try_setter)]
+    ports: Vec<u16>,
+}
+
+#[derive(Builder)]
+#[builder(setter(into), try_setter)]
+pub struct Server {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into), try_setter)]
+pub struct Job {
+    name: String,
+    priority: u8,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir("..")
+        .try_niceness(-5i64)
+        .unwrap()
+        .try_port(8080u32)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.niceness, -5);
+    assert_eq!(command.ports, vec![8080]);
+
+    let mut builder = Command::builder();
+    assert!(builder.try_niceness(300).is_err());
+    assert!(builder.try_port(-1).is_err());
+
+    let server = Server::builder()
+        .host("localhost")
+        .try_port(8080u64)
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+
+    let job = Job::builder()
+        .name("backup")
+        .tag("nightly")
+        .try_priority(3u32)
+        .unwrap()
+        .build();
+    assert_eq!(job.name, "backup");
+    assert_eq!(job.priority, 3);
+    assert_eq!(job.tags, vec!["nightly"]);
+
+    assert!(Job::builder().try_priority(u32::try_from(1000u64).unwrap()).is_err());
+}
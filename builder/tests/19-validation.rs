@@ -0,0 +1,104 @@
+// Beyond checking that required fields are present, build can enforce
+// relationships between fields:
+//
+//   - #[builder(requires = "other")]: if this field is set, `other` must be too
+//   - #[builder(conflicts_with = "other")]: this field and `other` cannot both
+//     be set
+//   - #[builder(group = "name", exactly_one)]: of all fields in the group,
+//     exactly one must be set (also at_least_one and at_most_one)
+//
+// and #[builder(build_fn(validate = "path"))] runs a custom check on the
+// builder. All violations are reported together in
+// CommandBuilderError::ValidationFailed, along with any missing fields. A
+// builder that is only missing fields reports MissingFields.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(build_fn(validate = "validate_connection"))]
+pub struct Connection {
+    host: String,
+    port: u16,
+    #[builder(group = "auth", exactly_one)]
+    token: Option<String>,
+    #[builder(group = "auth", requires = "password")]
+    username: Option<String>,
+    #[builder(group = "auth")]
+    password: Option<String>,
+    #[builder(conflicts_with = "insecure")]
+    certificate: Option<String>,
+    insecure: Option<bool>,
+    #[builder(each = "mirror", group = "replicas", at_most_one)]
+    mirrors: Vec<String>,
+    #[builder(group = "replicas")]
+    replica_of: Option<String>,
+}
+
+fn validate_connection(builder: &ConnectionBuilder) -> Result<(), String> {
+    match builder.port {
+        Some(0) => Err("port must not be zero".to_owned()),
+        _ => Ok(()),
+    }
+}
+
+fn main() {
+    let connection = Connection::builder()
+        .host("localhost".to_owned())
+        .port(5432)
+        .token("secret".to_owned())
+        .certificate("ca.pem".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(connection.token.as_deref(), Some("secret"));
+
+    let error = Connection::builder()
+        .host("localhost".to_owned())
+        .port(0)
+        .token("secret".to_owned())
+        .username("admin".to_owned())
+        .certificate("ca.pem".to_owned())
+        .insecure(true)
+        .mirror("replica-1".to_owned())
+        .replica_of("primary".to_owned())
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ConnectionBuilderError::ValidationFailed(vec![
+            "`username` requires `password` to be set".to_owned(),
+            "`certificate` conflicts with `insecure`".to_owned(),
+            "exactly one of `token`, `username`, `password` must be set".to_owned(),
+            "at most one of `mirrors`, `replica_of` may be set".to_owned(),
+            "port must not be zero".to_owned(),
+        ]),
+    );
+
+    let error = Connection::builder()
+        .host("localhost".to_owned())
+        .port(5432)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "validation failed: exactly one of `token`, `username`, `password` must be set",
+    );
+
+    let error = Connection::builder().token("secret".to_owned()).build().unwrap_err();
+    assert_eq!(error, ConnectionBuilderError::MissingFields(vec!["host", "port"]));
+
+    let error = Connection::builder()
+        .port(0)
+        .certificate("ca.pem".to_owned())
+        .insecure(true)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        ConnectionBuilderError::ValidationFailed(vec![
+            "missing field `host`".to_owned(),
+            "`certificate` conflicts with `insecure`".to_owned(),
+            "exactly one of `token`, `username`, `password` must be set".to_owned(),
+            "port must not be zero".to_owned(),
+        ]),
+    );
+}
@@ -0,0 +1,19 @@
+// Constraints that refer to a field that does not exist, and a `validate`
+// function that is not a path, are reported at compile time.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Connection {
+    #[builder(requires = "passwrd")]
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(validate = "check config"))]
+pub struct Config {
+    name: String,
+}
+
+fn main() {}
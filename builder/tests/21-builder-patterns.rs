@@ -0,0 +1,73 @@
+// #[builder(pattern = "owned")] generates setters that take and return the
+// builder by value and a `build(self)` that moves the collected values into
+// the struct, so fields do not need to implement Clone.
+//
+// #[builder(pattern = "immutable")] generates setters that take `&self` and
+// return a modified copy, leaving the original builder untouched.
+//
+// The default remains the `&mut self` pattern.
+
+use derive_builder::Builder;
+use std::sync::mpsc::{self, Receiver};
+
+pub struct Handle {
+    id: u32,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Worker {
+    name: String,
+    handle: Handle,
+    inbox: Option<Receiver<String>>,
+    #[builder(each = "job")]
+    jobs: Vec<Handle>,
+    #[builder(try_setter)]
+    priority: u8,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let (sender, receiver) = mpsc::channel();
+    sender.send("hello".to_owned()).unwrap();
+
+    let worker = Worker::builder()
+        .name("worker".to_owned())
+        .handle(Handle { id: 1 })
+        .inbox(receiver)
+        .job(Handle { id: 2 })
+        .job(Handle { id: 3 })
+        .try_priority(7u64)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    assert_eq!(worker.name, "worker");
+    assert_eq!(worker.handle.id, 1);
+    assert_eq!(worker.inbox.unwrap().recv().unwrap(), "hello");
+    assert_eq!(worker.jobs.iter().map(|job| job.id).collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(worker.priority, 7);
+
+    let base = Command::builder().executable("cargo".to_owned()).arg("build".to_owned());
+    let release = base.arg("--release".to_owned());
+    let nested = base.current_dir("..".to_owned());
+
+    let base = base.build().unwrap();
+    let release = release.build().unwrap();
+    let nested = nested.build().unwrap();
+
+    assert_eq!(base.args, vec!["build"]);
+    assert_eq!(release.args, vec!["build", "--release"]);
+    assert_eq!(nested.args, vec!["build"]);
+    assert!(base.current_dir.is_none());
+    assert_eq!(nested.current_dir.as_deref(), Some(".."));
+    assert_eq!(release.executable, "cargo");
+}
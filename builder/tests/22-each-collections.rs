@@ -0,0 +1,63 @@
+// The `each` attribute is not limited to Vec. It also works for VecDeque,
+// HashSet, BTreeSet and other sequences, for HashMap and BTreeMap (where the
+// one-at-a-time setter takes a key and a value), and for any other type
+// implementing Extend + Default, in which case the setter accepts anything
+// the collection can be extended with.
+
+use derive_builder::Builder;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+
+#[derive(Clone, Default)]
+pub struct Counter {
+    total: usize,
+}
+
+impl<T> Extend<T> for Counter {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.total += iter.into_iter().count();
+    }
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: VecDeque<String>,
+    #[builder(each = "env", setter(into))]
+    env: HashMap<String, String>,
+    #[builder(each = "label")]
+    labels: BTreeMap<&'static str, u32>,
+    #[builder(each = "feature", setter(into))]
+    features: HashSet<String>,
+    #[builder(each = "tag")]
+    tags: BTreeSet<u8>,
+    #[builder(each = "event")]
+    events: Counter,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .env("RUST_LOG", "debug")
+        .env("RUST_BACKTRACE", "1")
+        .label("retries", 3)
+        .feature("serde")
+        .feature("serde")
+        .tag(2)
+        .tag(1)
+        .event("started")
+        .event(42)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build", "--release"]);
+    assert_eq!(command.env.len(), 2);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.labels[&"retries"], 3);
+    assert_eq!(command.features.len(), 1);
+    assert_eq!(command.tags.into_iter().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(command.events.total, 2);
+}
@@ -0,0 +1,56 @@
+// Alongside the one-at-a-time `each` setter, a repeated field also gets a
+// setter replacing the whole collection (unless it would have the same name
+// as the `each` setter) and an `_extend` method appending everything from an
+// iterator. `each` also works on an `Option` wrapping a collection, which
+// stays `None` until the first element is added.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: HashMap<String, String>,
+    #[builder(each = "path", setter(into))]
+    paths: Vec<String>,
+    #[builder(each = "feature")]
+    features: Option<Vec<String>>,
+    #[builder(each = "target")]
+    targets: Option<Vec<String>>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("ignored".to_owned())
+        .args(vec!["build".to_owned()])
+        .arg("--release".to_owned())
+        .args_extend(vec!["--locked".to_owned(), "--offline".to_owned()])
+        .env_extend(vec![("RUST_LOG".to_owned(), "debug".to_owned())])
+        .paths_extend(["src", "tests"])
+        .path("benches")
+        .feature("serde".to_owned())
+        .features_extend(Some("std".to_owned()))
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build", "--release", "--locked", "--offline"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.paths, ["src", "tests", "benches"]);
+    assert_eq!(command.features.unwrap(), ["serde", "std"]);
+    assert_eq!(command.targets, None);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .targets(vec!["wasm32-unknown-unknown".to_owned()])
+        .target("x86_64-unknown-linux-gnu".to_owned())
+        .build()
+        .unwrap();
+
+    assert!(command.args.is_empty());
+    assert_eq!(command.targets.unwrap(), ["wasm32-unknown-unknown", "x86_64-unknown-linux-gnu"]);
+}
@@ -0,0 +1,85 @@
+// An existing struct can be turned back into a builder holding all of its
+// values, including `each` collections, so a copy differing in a few fields
+// does not have to restate the rest. `From<Struct>` is implemented for every
+// builder; `to_builder(&self)` is generated when the builder pattern already
+// clones its fields, i.e. for everything except the owned and typestate
+// builders. Those builders implement `From` by cloning too, so a struct
+// implementing `Drop` can still derive a builder.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Lock<T> {
+    path: String,
+    owner: T,
+}
+
+impl<T> Drop for Lock<T> {
+    fn drop(&mut self) {}
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Pair(String, Option<u8>);
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+    #[builder(each = "step")]
+    steps: Vec<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env(vec![])
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    let release = command
+        .to_builder()
+        .arg("--release".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(release.executable, "cargo");
+    assert_eq!(release.args, ["build", "--release"]);
+    assert_eq!(release.current_dir.as_deref(), Some(".."));
+
+    let test = CommandBuilder::from(command)
+        .executable("cargo-nextest".to_owned())
+        .env(vec!["RUST_LOG=debug".to_owned()])
+        .build()
+        .unwrap();
+    assert_eq!(test.executable, "cargo-nextest");
+    assert_eq!(test.args, ["build"]);
+    assert_eq!(test.env, ["RUST_LOG=debug"]);
+    assert_eq!(test.current_dir.as_deref(), Some(".."));
+
+    let lock = Lock { path: "/run/app.lock".to_owned(), owner: 1 };
+    let lock = LockBuilder::from(lock).owner(2).build().unwrap();
+    assert_eq!((lock.path.as_str(), lock.owner), ("/run/app.lock", 2));
+
+    let pair = PairBuilder::from(Pair("a".to_owned(), Some(1)))
+        ._1(2)
+        .build()
+        .unwrap();
+    assert_eq!(pair.0, "a");
+    assert_eq!(pair.1, Some(2));
+
+    let job = Job::builder().name("ci".to_owned()).step("lint".to_owned()).build();
+    let job = JobBuilder::from(job).step("test".to_owned()).build();
+    assert_eq!(job.name, "ci");
+    assert_eq!(job.steps, ["lint", "test"]);
+}
@@ -0,0 +1,81 @@
+// Builders can be layered with `merge`: every field set in `other` overrides
+// the one in `self`. The per-field `merge` attribute picks another strategy,
+// `append` to extend an `each` collection instead of replacing it and
+// `keep_first` to only fill in a field that is still unset.
+//
+// `merge` is generated for `#[builder(merge)]` or when a field has a
+// strategy, so without either a field may still be called `merge`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", merge = "append")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    #[builder(merge = "keep_first")]
+    current_dir: Option<String>,
+    #[builder(each = "feature", merge = "append")]
+    features: Option<Vec<String>>,
+}
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Remote {
+    url: String,
+    branch: Option<String>,
+}
+
+#[derive(Builder)]
+pub struct Strategy {
+    merge: bool,
+}
+
+fn main() {
+    let mut defaults = Command::builder();
+    defaults
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG=info".to_owned())
+        .feature("std".to_owned());
+
+    let mut file = Command::builder();
+    file.arg("--locked".to_owned()).current_dir("/src".to_owned());
+
+    let mut cli = Command::builder();
+    cli.executable("cargo-nightly".to_owned())
+        .arg("--release".to_owned())
+        .env("RUST_LOG=debug".to_owned())
+        .current_dir("/tmp".to_owned())
+        .feature("serde".to_owned());
+
+    let command = defaults.merge(file).merge(cli).build().unwrap();
+
+    assert_eq!(command.executable, "cargo-nightly");
+    assert_eq!(command.args, ["build", "--locked", "--release"]);
+    assert_eq!(command.env, ["RUST_LOG=debug"]);
+    assert_eq!(command.current_dir.as_deref(), Some("/src"));
+    assert_eq!(command.features.unwrap(), ["std", "serde"]);
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .merge(Command::builder())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert!(command.env.is_empty());
+    assert_eq!(command.features, None);
+
+    let mut origin = Remote::builder();
+    origin.url("https://example.com/repo.git".to_owned()).branch("main".to_owned());
+    let mut fork = Remote::builder();
+    fork.url("https://example.com/fork.git".to_owned());
+    let remote = origin.merge(fork).build().unwrap();
+    assert_eq!(remote.url, "https://example.com/fork.git");
+    assert_eq!(remote.branch.as_deref(), Some("main"));
+
+    assert!(Strategy::builder().merge(true).build().unwrap().merge);
+}
@@ -0,0 +1,18 @@
+// The merge strategy must be one of `replace`, `append` or `keep_first`, and
+// `append` only makes sense on an `each` collection.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(merge = "prepend")]
+    executable: String,
+}
+
+#[derive(Builder)]
+pub struct Job {
+    #[builder(merge = "append")]
+    steps: Vec<String>,
+}
+
+fn main() {}
@@ -0,0 +1,103 @@
+// #[builder(env = "...")] makes build() fall back to an environment variable
+// when a field was not set, parsing it with FromStr. A value that fails to
+// parse is reported as {Name}BuilderError::InvalidEnvVar together with the
+// variable name.
+//
+// #[builder(env_prefix = "...")] on the struct does the same for every field,
+// naming the variable after the prefix and the uppercased field name. An
+// explicit `env` on a field takes precedence over the prefix.
+//
+// Constraints such as `requires` and `conflicts_with` count a field read from
+// the environment as set.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(env = "BUILDER_TEST_EXECUTABLE")]
+    executable: String,
+    #[builder(env = "BUILDER_TEST_JOBS")]
+    jobs: Option<u32>,
+    #[builder(env = "BUILDER_TEST_UNSET", default = "3")]
+    retries: u8,
+}
+
+#[derive(Builder, Debug)]
+#[builder(env_prefix = "BUILDER_TEST_SERVER_")]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(env = "BUILDER_TEST_SERVER_NAME")]
+    name: Option<String>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Proxy {
+    #[builder(requires = "port")]
+    host: Option<String>,
+    #[builder(env = "BUILDER_TEST_PROXY_PORT")]
+    port: Option<u16>,
+    #[builder(env = "BUILDER_TEST_PROXY_DIRECT", conflicts_with = "host")]
+    direct: Option<bool>,
+}
+
+fn main() {
+    std::env::set_var("BUILDER_TEST_EXECUTABLE", "cargo");
+    std::env::set_var("BUILDER_TEST_JOBS", "8");
+
+    let command = Command::builder().build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.jobs, Some(8));
+    assert_eq!(command.retries, 3);
+
+    let command = Command::builder()
+        .executable("rustc".to_owned())
+        .jobs(1)
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "rustc");
+    assert_eq!(command.jobs, Some(1));
+
+    std::env::set_var("BUILDER_TEST_JOBS", "many");
+    let error = Command::builder().build().err().unwrap();
+    assert_eq!(
+        error,
+        CommandBuilderError::InvalidEnvVar {
+            variable: "BUILDER_TEST_JOBS",
+            error: "invalid digit found in string".to_owned(),
+        },
+    );
+    assert_eq!(
+        error.to_string(),
+        "invalid value in environment variable BUILDER_TEST_JOBS: invalid digit found in string",
+    );
+
+    let error = Server::builder().build().unwrap_err();
+    assert_eq!(error, ServerBuilderError::MissingFields(vec!["host", "port"]));
+
+    std::env::set_var("BUILDER_TEST_SERVER_HOST", "localhost");
+    std::env::set_var("BUILDER_TEST_SERVER_PORT", "8080");
+    std::env::set_var("BUILDER_TEST_SERVER_NAME", "api");
+
+    let server = Server::builder().port(9090).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 9090);
+    assert_eq!(server.name.as_deref(), Some("api"));
+
+    let error = Proxy::builder().host("proxy".to_owned()).build().unwrap_err();
+    assert_eq!(
+        error,
+        ProxyBuilderError::ValidationFailed(vec!["`host` requires `port` to be set".to_owned()]),
+    );
+
+    std::env::set_var("BUILDER_TEST_PROXY_PORT", "3128");
+    let proxy = Proxy::builder().host("proxy".to_owned()).build().unwrap();
+    assert_eq!(proxy.port, Some(3128));
+
+    std::env::set_var("BUILDER_TEST_PROXY_DIRECT", "true");
+    let error = Proxy::builder().host("proxy".to_owned()).build().unwrap_err();
+    assert_eq!(
+        error,
+        ProxyBuilderError::ValidationFailed(vec!["`direct` conflicts with `host`".to_owned()]),
+    );
+}
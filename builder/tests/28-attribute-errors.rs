@@ -0,0 +1,29 @@
+// Every malformed #[builder] attribute is reported at once with a span
+// pointing at it: unknown keys (with a suggestion when one is close), values
+// of the wrong shape, keys given twice, and `each` on a field that is not a
+// collection. Strings holding a path, visibility or type must parse as one.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "borrowed", build_fn(eror = "String"))]
+pub struct Command {
+    #[builder(each)]
+    executable: String,
+    #[builder(each = "arg", each = "argument")]
+    args: Vec<String>,
+    #[builder(each = "level")]
+    verbosity: u8,
+    #[builder(default = 3, colour = "red")]
+    retries: u8,
+    #[builder(setter(into = "yes"))]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(build_fn(error = "not a type!!"))]
+pub struct Output {
+    status: i32,
+}
+
+fn main() {}
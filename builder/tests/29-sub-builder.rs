@@ -0,0 +1,74 @@
+// #[builder(sub_builder)] on a field whose type derives Builder stores the
+// field's own builder instead of an Option. It is reachable through
+// `{field}_mut()` and built recursively by `build()`. When anything is missing
+// inside it, MissingFields lists every missing field by its path from the
+// outer struct, so the missing fields of a struct with sub-builders are
+// Strings. The regular setter still accepts a complete value, and
+// `to_builder()` turns the nested value back into its builder.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Tls {
+    cert: String,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Server {
+    host: String,
+    port: u16,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder, Debug)]
+pub struct Config {
+    name: String,
+    #[builder(sub_builder)]
+    server: Server,
+}
+
+fn main() {
+    let mut builder = Config::builder();
+    builder.name("api".to_owned());
+    builder.server_mut().host("localhost".to_owned()).port(8080);
+    builder.server_mut().tls_mut().cert("cert.pem".to_owned());
+
+    let config = builder.build().unwrap();
+    assert_eq!(config.name, "api");
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.server.tls.cert, "cert.pem");
+
+    let mut builder = Config::builder();
+    builder.server_mut().host("localhost".to_owned());
+
+    let error = builder.build().unwrap_err();
+    assert_eq!(
+        error,
+        ConfigBuilderError::MissingFields(vec![
+            "name".to_owned(),
+            "server.port".to_owned(),
+            "server.tls.cert".to_owned(),
+        ]),
+    );
+    assert_eq!(error.to_string(), "missing fields: name, server.port, server.tls.cert");
+
+    let mut builder = Config::builder();
+    builder.server(Server::builder().host("localhost".to_owned()).port(8080).tls(config.server.tls).build().unwrap());
+    assert_eq!(builder.build().unwrap_err(), ConfigBuilderError::MissingFields(vec!["name".to_owned()]));
+
+    builder.name("copy".to_owned());
+    builder.server_mut().port(9090);
+    let copy = builder.build().unwrap();
+    assert_eq!(copy.server.host, "localhost");
+    assert_eq!(copy.server.port, 9090);
+    assert_eq!(copy.server.tls.cert, "cert.pem");
+
+    let mut builder = copy.to_builder();
+    builder.server_mut().tls_mut().cert("other.pem".to_owned());
+    let other = builder.build().unwrap();
+    assert_eq!(other.name, "copy");
+    assert_eq!(other.server.port, 9090);
+    assert_eq!(other.server.tls.cert, "other.pem");
+}
@@ -0,0 +1,50 @@
+// #[builder(no_std)] expands to code that only uses `core` and `alloc`, for
+// `#![no_std]` crates that declare `extern crate alloc`. Errors implement
+// core::error::Error and `env` fallbacks are not available.
+//
+// #[builder(crate = "...")] points the allocating parts of the expansion
+// (Vec, String, format!) at another path, for crates that re-export `alloc`
+// or `std` under a different name.
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+pub mod reexports {
+    pub use ::alloc::*;
+}
+
+#[derive(Builder)]
+#[builder(no_std)]
+pub struct Command {
+    executable: alloc::string::String,
+    #[builder(each = "arg")]
+    args: alloc::vec::Vec<alloc::string::String>,
+    current_dir: core::option::Option<alloc::string::String>,
+}
+
+#[derive(Builder)]
+#[builder(crate = "crate::reexports")]
+pub struct Server {
+    host: alloc::string::String,
+    #[builder(default = "8080")]
+    port: u16,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".into())
+        .arg("build".into())
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, ["build"]);
+    assert!(command.current_dir.is_none());
+
+    let error: &dyn core::error::Error = &Command::builder().build().err().unwrap();
+    assert_eq!(alloc::string::ToString::to_string(error), "missing fields: executable");
+
+    let server = Server::builder().host("localhost".into()).build().unwrap();
+    assert_eq!(server.host, "localhost");
+    assert_eq!(server.port, 8080);
+}
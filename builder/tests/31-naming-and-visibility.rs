@@ -0,0 +1,83 @@
+// The builder's name, its visibility and the names of the generated methods
+// can all be customised. By default the builder is exactly as visible as the
+// struct it builds, so a private struct no longer forces a `pub` builder; a
+// different visibility can be given with `vis`.
+//
+// Fields marked `private` get setters visible only inside the defining
+// module, and `skip`ped fields get no setter or storage at all, being filled
+// from their `default` (or `Default::default()`) when building. A generic
+// parameter used only by skipped fields is still accepted.
+
+mod config {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(name = "ConfigOptions", vis = "pub(crate)", build_fn(name = "finish"), setter(prefix = "with_"))]
+    pub struct Config {
+        pub host: String,
+        #[builder(setter(name = "port_number"))]
+        pub port: u16,
+        #[builder(private)]
+        pub secret: Option<String>,
+        #[builder(skip, default = "3")]
+        pub retries: u32,
+        #[builder(skip)]
+        pub cache: Vec<String>,
+    }
+
+    impl ConfigOptions {
+        pub fn with_default_secret(&mut self) -> &mut Self {
+            self.with_secret("hunter2".to_owned())
+        }
+    }
+
+    #[derive(Builder)]
+    pub struct Tagged<T> {
+        pub name: String,
+        #[builder(skip)]
+        pub marker: ::std::marker::PhantomData<T>,
+    }
+
+    #[derive(Builder)]
+    struct Hidden {
+        value: u8,
+    }
+
+    pub fn hidden_value() -> u8 {
+        Hidden::builder().value(7).build().unwrap().value
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate, build_fn(name = "done"), setter(prefix = "set_"))]
+    pub struct Point {
+        pub x: i32,
+        #[builder(setter(name = "ordinate"))]
+        pub y: i32,
+    }
+}
+
+use config::{Config, ConfigOptions, Point, Tagged};
+
+fn main() {
+    let mut options: ConfigOptions = Config::builder();
+    let config = options
+        .with_host("localhost".to_owned())
+        .port_number(8080)
+        .with_default_secret()
+        .finish()
+        .unwrap();
+
+    assert_eq!(config.host, "localhost");
+    assert_eq!(config.port, 8080);
+    assert_eq!(config.secret.as_deref(), Some("hunter2"));
+    assert_eq!(config.retries, 3);
+    assert!(config.cache.is_empty());
+
+    assert_eq!(config::hidden_value(), 7);
+
+    let tagged = Tagged::<fn()>::builder().name("unit".to_owned()).build().unwrap();
+    assert_eq!(tagged.name, "unit");
+
+    let point = Point::builder().ordinate(2).set_x(1).done();
+    assert_eq!((point.x, point.y), (1, 2));
+}
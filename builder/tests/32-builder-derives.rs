@@ -0,0 +1,64 @@
+// Traits listed in `derive(...)` are derived on the generated builder, so a
+// half-built builder can be cloned, compared and debug-printed. Arbitrary
+// attributes can be passed through to the builder struct with
+// `struct_attr(...)` and to its fields with `field_attr(...)`.
+//
+// The immutable pattern already derives `Clone` on its builder; naming it in
+// `derive(...)` as well must not derive it twice.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(derive(Debug, Clone, PartialEq), struct_attr(doc = "Builds a `Command`."))]
+#[builder(struct_attr(cfg_attr(test, derive(Default))))]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg", field_attr(doc = "Arguments collected so far."))]
+    args: Vec<String>,
+    #[builder(field_attr(allow(dead_code)))]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable", derive(Clone, Debug))]
+pub struct Pair {
+    left: u8,
+    right: u8,
+}
+
+#[derive(Builder)]
+#[builder(typestate, derive(Debug, Clone))]
+pub struct Point {
+    #[builder(field_attr(doc = "The abscissa."))]
+    x: i32,
+    y: i32,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned()).arg("build".to_owned());
+
+    let snapshot = builder.clone();
+    assert_eq!(snapshot, builder);
+    let debug = format!("{:?}", snapshot);
+    assert!(debug.contains("CommandBuilder"));
+    assert!(debug.contains("\"build\""));
+
+    builder.arg("--release".to_owned());
+    assert_ne!(snapshot, builder);
+
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, None);
+
+    let half = Pair::builder().left(1);
+    let pair = half.clone().right(2).build().unwrap();
+    assert_eq!((pair.left, pair.right), (1, 2));
+    assert!(format!("{:?}", half).contains("PairBuilder"));
+
+    let half = Point::builder().x(1);
+    let point = half.clone().y(2).build();
+    assert_eq!((point.x, point.y), (1, 2));
+    assert!(format!("{:?}", half).contains("PointBuilder"));
+}
@@ -0,0 +1,82 @@
+// Builders can be inspected and partially undone. Every field gets
+// `get_field`, `is_field_set` and `clear_field`; `each` collections also get
+// `field_len`. A getter returns `Option<&T>` for scalar and `Option` fields,
+// and a plain reference for `each` collections and sub-builders, which always
+// hold a value.
+//
+// `clear_field` follows the builder pattern like a setter does, and puts the
+// field back into the state it had in a fresh builder.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    envs: HashMap<String, String>,
+    current_dir: Option<String>,
+    #[builder(default = "3")]
+    retries: u8,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Pair {
+    left: u8,
+    right: u8,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Point {
+    x: i32,
+    label: Option<String>,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert!(!builder.is_executable_set());
+    assert_eq!(builder.get_executable(), None);
+    assert_eq!(builder.args_len(), 0);
+
+    builder
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .arg("--release".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
+        .current_dir("..".to_owned());
+
+    assert!(builder.is_executable_set());
+    assert_eq!(builder.get_executable().map(String::as_str), Some("cargo"));
+    assert_eq!(builder.get_args(), &["build", "--release"]);
+    assert_eq!(builder.args_len(), 2);
+    assert_eq!(builder.envs_len(), 1);
+    assert_eq!(builder.get_current_dir().map(String::as_str), Some(".."));
+    assert!(!builder.is_retries_set());
+    assert_eq!(builder.get_retries(), None);
+
+    builder.clear_args().clear_current_dir();
+    assert!(!builder.is_args_set());
+    assert_eq!(builder.args_len(), 0);
+    assert_eq!(builder.get_current_dir(), None);
+
+    let command = builder.build().unwrap();
+    assert!(command.args.is_empty());
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.retries, 3);
+
+    builder.clear_executable();
+    assert!(builder.build().is_err());
+
+    let pair = Pair::builder().left(1).clear_left().right(2);
+    assert!(!pair.is_left_set());
+    assert_eq!(pair.get_right(), Some(&2));
+
+    let point = Point::builder().label("origin".to_owned());
+    assert!(point.is_label_set());
+    let point = point.clear_label().x(0).build();
+    assert_eq!(point.label, None);
+}
@@ -0,0 +1,145 @@
+// `#[builder]` on a function generates a builder for its arguments, so a long
+// argument list is filled in by name instead of by position. `connect` gets
+// a `connect_builder()` function returning a `ConnectBuilder` whose `call()`
+// builds the arguments and invokes `connect` with them.
+//
+// Parameters are classified like struct fields: `Option` parameters may be
+// left out, and field attributes such as `each` and `default` are accepted on
+// parameters. Attributes on `#[builder(...)]` itself configure the builder
+// like container attributes do.
+//
+// Elided lifetimes in parameter types, as in `&str`, become lifetime
+// parameters of the builder, and so does `'_`, as in `Cow<'_, str>`. Generic
+// parameters need not appear in any parameter type.
+//
+// Associated functions get a builder when their `impl` block is marked with
+// `#[builder]` as well. The builder is named after the type and the function,
+// and is created by an associated function next to the original one. For a
+// method taking `self`, `&self` or `&mut self`, that function takes the same
+// receiver, and `call` passes it on. The receiver is stored like any other
+// parameter, so `self` and `&mut self` need `pattern = "owned"` unless they
+// can be cloned.
+
+use derive_builder::builder;
+use std::borrow::Cow;
+use std::time::Duration;
+
+#[derive(Debug, PartialEq)]
+pub struct Connection {
+    host: String,
+    port: u16,
+    timeout: Option<Duration>,
+    headers: Vec<String>,
+}
+
+#[builder]
+pub fn connect(
+    host: String,
+    #[builder(default = "80")] port: u16,
+    timeout: Option<Duration>,
+    #[builder(each = "header")] headers: Vec<String>,
+) -> Connection {
+    Connection { host, port, timeout, headers }
+}
+
+#[builder(pattern = "owned", setter(into))]
+fn greet(name: String, greeting: Option<String>) -> String {
+    format!("{}, {}!", greeting.as_deref().unwrap_or("Hello"), name)
+}
+
+#[builder]
+fn join(parts: &[&str], separator: Option<&str>) -> String {
+    parts.join(separator.unwrap_or("/"))
+}
+
+#[builder]
+fn shout(text: Cow<'_, str>) -> String {
+    text.to_uppercase()
+}
+
+#[builder]
+fn parse<T: std::str::FromStr>(text: String) -> Option<T> {
+    text.parse().ok()
+}
+
+pub struct Client {
+    base: String,
+}
+
+#[builder]
+impl Client {
+    #[builder]
+    pub fn new(base: String, version: Option<u8>) -> Self {
+        Client { base: format!("{}/v{}", base, version.unwrap_or(1)) }
+    }
+
+    #[builder]
+    pub fn with_path<'a>(base: &'a str, path: &str) -> Self {
+        Client { base: format!("{}/{}", base, path) }
+    }
+
+    #[builder]
+    pub fn request(&self, path: &str, #[builder(default = "1")] attempts: u8) -> String {
+        format!("GET {}/{} x{}", self.base, path, attempts)
+    }
+
+    #[builder(pattern = "owned")]
+    pub fn rebase(&mut self, base: String) {
+        self.base = base;
+    }
+
+    #[builder(pattern = "owned")]
+    pub fn into_base(self, suffix: Option<String>) -> String {
+        self.base + suffix.as_deref().unwrap_or("")
+    }
+
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+}
+
+fn main() {
+    let connection = connect_builder()
+        .host("localhost".to_owned())
+        .header("Accept: */*".to_owned())
+        .timeout(Duration::from_secs(5))
+        .call()
+        .unwrap();
+
+    assert_eq!(connection, Connection {
+        host: "localhost".to_owned(),
+        port: 80,
+        timeout: Some(Duration::from_secs(5)),
+        headers: vec!["Accept: */*".to_owned()],
+    });
+
+    let err = connect_builder().call().unwrap_err();
+    assert_eq!(err, ConnectBuilderError::MissingFields(vec!["host"]));
+
+    assert_eq!(greet_builder().name("world").call().unwrap(), "Hello, world!");
+    assert_eq!(greet_builder().name("you").greeting("Hi").call().unwrap(), "Hi, you!");
+
+    let client = Client::new_builder().base("https://example.com".to_owned()).version(2).call().unwrap();
+    assert_eq!(client.base(), "https://example.com/v2");
+    assert_eq!(Client::new("local".to_owned(), None).base(), "local/v1");
+
+    let parts = vec!["usr".to_owned(), "local".to_owned()];
+    let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+    assert_eq!(join_builder().parts(&parts).call().unwrap(), "usr/local");
+    assert_eq!(join_builder().parts(&parts).separator(".").call().unwrap(), "usr.local");
+
+    assert_eq!(shout_builder().text(Cow::Borrowed("hey")).call().unwrap(), "HEY");
+
+    let number: Option<u16> = parse_builder().text("8080".to_owned()).call().unwrap();
+    assert_eq!(number, Some(8080));
+    assert_eq!(parse_builder::<u8>().text("-1".to_owned()).call().unwrap(), None);
+
+    let client = Client::with_path_builder().base("https://example.com").path("api").call().unwrap();
+    assert_eq!(client.base(), "https://example.com/api");
+
+    assert_eq!(client.request_builder().path("users").call().unwrap(), "GET https://example.com/api/users x1");
+    let mut client = client;
+    client.rebase_builder().base("http://localhost".to_owned()).call().unwrap();
+    assert_eq!(client.request_builder().path("").attempts(3).call().unwrap(), "GET http://localhost/ x3");
+    assert_eq!(client.into_base_builder().suffix("/".to_owned()).call().unwrap(), "http://localhost/");
+}
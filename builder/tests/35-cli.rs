@@ -0,0 +1,107 @@
+// With `#[builder(cli)]` the builder gets `parse_args`, filling it from
+// command-line arguments (without the program name). Each field is set by a
+// `--flag` named after it, or after its `each` setter for collections, and
+// `long`/`short` pick other names. Values are parsed with `FromStr`; `bool`
+// fields are switches, `each` flags can be repeated and maps take
+// `KEY=VALUE`. Tuple struct fields have no name of their own, so they are
+// given one with `long` or `name`.
+//
+// Unknown flags, missing values and values that fail to parse are reported
+// as `InvalidArgument`, and `--help` returns `HelpRequested` carrying a
+// generated usage text.
+
+use derive_builder::Builder;
+use std::collections::HashMap;
+
+#[derive(Builder)]
+#[builder(cli, derive(Debug))]
+pub struct Command {
+    #[builder(short = 'e')]
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    envs: HashMap<String, u32>,
+    current_dir: Option<String>,
+    #[builder(long = "jobs", short = 'j', default = "1")]
+    parallelism: u8,
+    verbose: Option<bool>,
+}
+
+#[derive(Builder)]
+#[builder(cli, derive(Debug))]
+pub struct Range(#[builder(long = "from")] u32, #[builder(name = "to", short = 't')] u32);
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn main() {
+    let command = CommandBuilder::parse_args(args(&[
+        "-e", "cargo",
+        "--arg", "build",
+        "--arg=--release",
+        "--env", "RUST_MIN_STACK=8388608",
+        "--current-dir", "..",
+        "-j", "8",
+        "--verbose",
+    ]))
+    .unwrap()
+    .build()
+    .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.envs["RUST_MIN_STACK"], 8388608);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.parallelism, 8);
+    assert_eq!(command.verbose, Some(true));
+
+    let command = CommandBuilder::parse_args(args(&["--executable=rustc", "--verbose=false"]))
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(command.executable, "rustc");
+    assert!(command.args.is_empty());
+    assert_eq!(command.parallelism, 1);
+    assert_eq!(command.verbose, Some(false));
+
+    let err = CommandBuilder::parse_args(args(&["--jobs", "many"])).unwrap_err();
+    assert_eq!(err.to_string(), "invalid argument --jobs: invalid digit found in string");
+
+    let err = CommandBuilder::parse_args(args(&["--executable"])).unwrap_err();
+    assert_eq!(err.to_string(), "invalid argument --executable: missing value");
+
+    let err = CommandBuilder::parse_args(args(&["--env", "RUST_LOG"])).unwrap_err();
+    assert_eq!(err.to_string(), "invalid argument --env: expected KEY=VALUE");
+
+    let err = CommandBuilder::parse_args(args(&["build"])).unwrap_err();
+    assert_eq!(err.to_string(), "invalid argument build: unknown argument");
+
+    match CommandBuilder::parse_args(args(&["-e", "cargo", "--help"])).unwrap_err() {
+        CommandBuilderError::HelpRequested(help) => assert_eq!(help, "\
+Usage: command [OPTIONS]
+
+Options:
+  -e, --executable <EXECUTABLE>    (required)
+      --arg <ARG>...
+      --env <KEY=VALUE>...
+      --current-dir <CURRENT_DIR>
+  -j, --jobs <JOBS>
+      --verbose
+      --help                       Print this help
+"),
+        err => panic!("unexpected error: {}", err),
+    }
+
+    let range = RangeBuilder::parse_args(args(&["--from", "2", "-t", "5"]))
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!((range.0, range.1), (2, 5));
+
+    match RangeBuilder::parse_args(args(&["--help"])).unwrap_err() {
+        RangeBuilderError::HelpRequested(help) => assert!(help.contains("--from <FROM>")),
+        err => panic!("unexpected error: {}", err),
+    }
+}
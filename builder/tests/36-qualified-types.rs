@@ -0,0 +1,63 @@
+// `Option` and the standard collections are recognised when written with a
+// path through `std`, `core` or `alloc`, not only by their bare names. A
+// fully qualified `Option` field is optional and its setter takes the inner
+// type; a qualified `Vec` works with `each` like the bare one.
+//
+// Type aliases cannot be seen through, so the classification can be
+// overridden: `#[builder(optional)]` makes a field an optional one, and
+// `#[builder(required)]` makes an `Option` field required, with a setter
+// that takes the `Option` itself.
+
+extern crate alloc;
+
+use derive_builder::Builder;
+
+type MaybeDir = Option<String>;
+
+mod other {
+    pub type Option<T> = std::option::Option<T>;
+}
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: ::std::vec::Vec<String>,
+    #[builder(each = "env")]
+    env: std::collections::HashMap<String, String>,
+    #[builder(each = "feature")]
+    features: alloc::vec::Vec<String>,
+    stdin: core::option::Option<String>,
+    stdout: ::std::option::Option<String>,
+    #[builder(optional)]
+    current_dir: MaybeDir,
+    #[builder(required)]
+    timeout: Option<u64>,
+    #[builder(optional)]
+    nice: other::Option<i8>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
+        .feature("serde".to_owned())
+        .stdout("out.log".to_owned())
+        .current_dir("..".to_owned())
+        .timeout(None)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.args, vec!["build"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.features, vec!["serde"]);
+    assert_eq!(command.stdin, None);
+    assert_eq!(command.stdout.as_deref(), Some("out.log"));
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, None);
+    assert_eq!(command.nice, None);
+
+    let err = Command::builder().executable("cargo".to_owned()).build().err().unwrap();
+    assert_eq!(err.to_string(), "missing fields: timeout");
+}
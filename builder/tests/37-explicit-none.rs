@@ -0,0 +1,50 @@
+// Builders keep track of whether an `Option` field was explicitly set to
+// `None` or never set at all. The setter of an `Option<T>` field always takes
+// the inner `T`, so `setter(strip_option)` is accepted but changes nothing,
+// and `field_none()` sets the field to `None` explicitly.
+//
+// An explicit `None` counts as set: it wins over a `default`, is taken over
+// by `merge` and is kept by `keep_first`, while `clear_field()` returns the
+// field to being unset.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(setter(strip_option))]
+    current_dir: Option<String>,
+    #[builder(default = "Some(30)")]
+    timeout: Option<u64>,
+    #[builder(merge = "keep_first")]
+    nice: Option<i8>,
+}
+
+fn main() {
+    let command = Command::builder().executable("cargo".to_owned()).build().unwrap();
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.timeout, Some(30));
+
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned()).current_dir("..".to_owned()).timeout_none();
+    assert!(builder.is_timeout_set());
+    assert_eq!(builder.get_timeout(), None);
+
+    let command = builder.build().unwrap();
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+    assert_eq!(command.timeout, None);
+
+    builder.clear_timeout();
+    assert!(!builder.is_timeout_set());
+    assert_eq!(builder.build().unwrap().timeout, Some(30));
+
+    let mut base = Command::builder();
+    base.executable("cargo".to_owned()).current_dir("..".to_owned()).nice_none();
+    let mut overrides = Command::builder();
+    overrides.current_dir_none().nice(5);
+    base.merge(overrides);
+
+    let command = base.build().unwrap();
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.nice, None);
+}
@@ -0,0 +1,85 @@
+// With `setter(wrap)` the setter of a `Box`, `Rc`, `Arc` or `Cow` field takes
+// the value to be wrapped instead of the pointer. Pointers to trait objects
+// accept any implementor, pointers to `str` or slices and `Cow` accept
+// anything that converts into the field type, and other pointers take the
+// inner value (through `Into` with `setter(into)`).
+
+use derive_builder::Builder;
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::rc::Rc;
+use std::sync::Arc;
+
+pub trait Handler {
+    fn handle(&self, input: u32) -> u32;
+}
+
+pub struct Doubler;
+
+impl Handler for Doubler {
+    fn handle(&self, input: u32) -> u32 {
+        input * 2
+    }
+}
+
+#[derive(Builder)]
+pub struct Service {
+    #[builder(setter(wrap))]
+    handler: Arc<dyn Handler + Send + Sync>,
+    #[builder(setter(wrap))]
+    name: Cow<'static, str>,
+    #[builder(setter(wrap))]
+    label: Arc<str>,
+    #[builder(setter(wrap, into))]
+    description: Box<String>,
+    #[builder(setter(wrap))]
+    fallback: Option<Rc<dyn Fn(u32) -> u32>>,
+    #[builder(setter(wrap))]
+    debug: Rc<dyn Debug>,
+    #[builder(setter(wrap))]
+    weights: std::boxed::Box<[u8]>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Task {
+    #[builder(setter(wrap))]
+    run: Box<dyn FnOnce() -> String>,
+}
+
+fn main() {
+    let service = Service::builder()
+        .handler(Doubler)
+        .name("doubler")
+        .label("double")
+        .description("multiplies by two")
+        .fallback(|input| input + 1)
+        .debug(42)
+        .weights(vec![1, 2, 3])
+        .build()
+        .unwrap();
+
+    assert_eq!(service.handler.handle(21), 42);
+    assert_eq!(service.name, "doubler");
+    assert_eq!(&*service.label, "double");
+    assert_eq!(*service.description, "multiplies by two");
+    assert_eq!(service.fallback.as_ref().map(|fallback| fallback(1)), Some(2));
+    assert_eq!(format!("{:?}", service.debug), "42");
+    assert_eq!(&*service.weights, &[1, 2, 3]);
+
+    let owned = Service::builder()
+        .handler(Doubler)
+        .name(String::from("owned"))
+        .label(String::from("owned"))
+        .description(String::new())
+        .debug("debug")
+        .weights(vec![])
+        .build()
+        .unwrap();
+    assert!(matches!(owned.name, Cow::Owned(_)));
+    assert!(owned.fallback.is_none());
+
+    let greeting = String::from("hello");
+    let task = Task::builder().run(move || greeting).build().unwrap();
+    assert_eq!((task.run)(), "hello");
+}
@@ -0,0 +1,55 @@
+// `#[builder(const)]` makes the builder usable in constant expressions: the
+// builder function and setters are `const fn`, the builder uses the owned
+// pattern, and `build_const()` returns the struct directly. A missing field
+// makes `build_const()` panic, which inside a `static` or `const` is a
+// compile-time error (see the next test case).
+//
+// The ordinary `build()` is still generated for use at runtime.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(const)]
+pub struct Command {
+    executable: &'static str,
+    args: &'static [&'static str],
+    current_dir: Option<&'static str>,
+    #[builder(default = "3")]
+    retries: u8,
+    #[builder(skip, default = "false")]
+    dry_run: bool,
+}
+
+static DEFAULT_CMD: Command = Command::builder()
+    .executable("cargo")
+    .args(&["build", "--release"])
+    .build_const();
+
+const VERBOSE_CMD: Command = Command::builder()
+    .executable("cargo")
+    .args(&["test"])
+    .current_dir("..")
+    .retries(5)
+    .build_const();
+
+const NO_DIR_CMD: Command = Command::builder()
+    .executable("rustc")
+    .args(&[])
+    .current_dir("..")
+    .current_dir_none()
+    .build_const();
+
+fn main() {
+    assert_eq!(DEFAULT_CMD.executable, "cargo");
+    assert_eq!(DEFAULT_CMD.args, ["build", "--release"]);
+    assert_eq!(DEFAULT_CMD.current_dir, None);
+    assert_eq!(DEFAULT_CMD.retries, 3);
+    assert!(!DEFAULT_CMD.dry_run);
+
+    assert_eq!(VERBOSE_CMD.current_dir, Some(".."));
+    assert_eq!(VERBOSE_CMD.retries, 5);
+    assert_eq!(NO_DIR_CMD.current_dir, None);
+
+    let runtime = Command::builder().executable("cargo").args(&["build", "--release"]).build().unwrap();
+    assert_eq!(runtime, DEFAULT_CMD);
+}
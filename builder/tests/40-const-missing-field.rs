@@ -0,0 +1,15 @@
+// A const builder that is missing a required field fails to compile when
+// `build_const()` is evaluated in a constant, naming the missing field.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(const)]
+pub struct Command {
+    executable: &'static str,
+    args: &'static [&'static str],
+}
+
+static DEFAULT_CMD: Command = Command::builder().args(&["build"]).build_const();
+
+fn main() {}
@@ -0,0 +1,84 @@
+// Sub-builders work with every pattern of the outer builder. An owned builder
+// moves the nested builder out when building and an immutable one clones it,
+// so the nested builder has to be Clone like every other field of an
+// immutable builder.
+//
+// The nested struct may rename its build method, convert into its own error
+// type and have error variants of its own, e.g. from `cli`. Missing nested
+// fields are reported by path as usual, any other error of the nested builder
+// becomes a violation of the field in ValidationFailed.
+
+use derive_builder::Builder;
+
+#[derive(Debug, PartialEq)]
+pub struct LimitsError(String);
+
+impl From<LimitsBuilderError> for LimitsError {
+    fn from(error: LimitsBuilderError) -> Self {
+        LimitsError(error.to_string())
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(cli, derive(Clone), build_fn(name = "finish", error = "LimitsError", validate = "validate_limits"))]
+pub struct Limits {
+    connections: u32,
+    #[builder(default = "30")]
+    timeout: u64,
+}
+
+fn validate_limits(builder: &LimitsBuilder) -> Result<(), String> {
+    match builder.connections {
+        Some(0) => Err("connections must not be zero".to_owned()),
+        _ => Ok(()),
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "owned")]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug)]
+#[builder(pattern = "immutable")]
+pub struct Cluster {
+    nodes: u8,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+fn main() {
+    let mut builder = Service::builder().name("api".to_owned());
+    builder.limits_mut().connections(10);
+    let service = builder.build().unwrap();
+    assert_eq!(service.name, "api");
+    assert_eq!(service.limits.connections, 10);
+    assert_eq!(service.limits.timeout, 30);
+
+    let error = Service::builder().name("api".to_owned()).build().unwrap_err();
+    assert_eq!(error, ServiceBuilderError::MissingFields(vec!["limits.connections".to_owned()]));
+
+    let mut builder = Service::builder();
+    *builder.limits_mut() = LimitsBuilder::parse_args(vec!["--connections".to_owned(), "0".to_owned()]).unwrap();
+    let error = builder.build().unwrap_err();
+    assert_eq!(
+        error,
+        ServiceBuilderError::ValidationFailed(vec![
+            "missing field `name`".to_owned(),
+            "limits: validation failed: connections must not be zero".to_owned(),
+        ]),
+    );
+
+    let mut builder = Cluster::builder().nodes(3);
+    builder.limits_mut().connections(100).timeout(5);
+    let first = builder.build().unwrap();
+    let second = builder.nodes(5).build().unwrap();
+    assert_eq!((first.nodes, first.limits.connections, first.limits.timeout), (3, 100, 5));
+    assert_eq!((second.nodes, second.limits.connections), (5, 100));
+
+    let error = Limits::builder().finish().unwrap_err();
+    assert_eq!(error, LimitsError("missing fields: connections".to_owned()));
+}
@@ -0,0 +1,20 @@
+// A mutable builder builds its sub-builders through `&mut self`, so a nested
+// builder using the owned pattern, whose build method consumes it, cannot be
+// used there. The error points at the `sub_builder` attribute.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Limits {
+    connections: u32,
+}
+
+#[derive(Builder)]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+fn main() {}
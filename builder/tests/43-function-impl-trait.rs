@@ -0,0 +1,12 @@
+// A parameter becomes a field of the builder, and a field cannot have an
+// `impl Trait` type, so `#[builder]` rejects such parameters. A generic
+// parameter does the same job.
+
+use derive_builder::builder;
+
+#[builder]
+fn run(cmd: impl Into<String>, args: Vec<String>) -> String {
+    format!("{} {}", cmd.into(), args.join(" "))
+}
+
+fn main() {}
@@ -0,0 +1,66 @@
+// Every flag's value type must implement `FromStr`. A field whose type does
+// not, or that should not be set from the command line, is marked
+// `skip_cli`: it gets no flag and is left to the builder's setters.
+//
+// A `cli` builder can still be a sub-builder of a builder that has no `cli`
+// of its own.
+
+use derive_builder::Builder;
+use std::time::Duration;
+
+#[derive(Builder, Debug)]
+#[builder(cli, derive(Debug))]
+pub struct Job {
+    name: String,
+    #[builder(skip_cli)]
+    timeout: Duration,
+    #[builder(skip_cli, default)]
+    tags: Vec<String>,
+}
+
+#[derive(Builder, Debug)]
+pub struct Pipeline {
+    #[builder(sub_builder)]
+    job: Job,
+    stages: u8,
+}
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn main() {
+    let job = JobBuilder::parse_args(args(&["--name", "test"]))
+        .unwrap()
+        .timeout(Duration::from_secs(60))
+        .build()
+        .unwrap();
+    assert_eq!(job.name, "test");
+    assert_eq!(job.timeout, Duration::from_secs(60));
+    assert!(job.tags.is_empty());
+
+    let err = JobBuilder::parse_args(args(&["--timeout", "60"])).unwrap_err();
+    assert_eq!(err.to_string(), "invalid argument --timeout: unknown argument");
+
+    match JobBuilder::parse_args(args(&["--help"])).unwrap_err() {
+        JobBuilderError::HelpRequested(help) => assert_eq!(help, "\
+Usage: job [OPTIONS]
+
+Options:
+      --name <NAME>  (required)
+      --help         Print this help
+"),
+        err => panic!("unexpected error: {}", err),
+    }
+
+    let mut builder = Pipeline::builder();
+    builder.stages(2);
+    builder.job_mut().name("lint".to_owned());
+    let err = builder.build().unwrap_err();
+    assert_eq!(err, PipelineBuilderError::MissingFields(vec!["job.timeout".to_owned()]));
+
+    builder.job_mut().timeout(Duration::from_secs(5));
+    let pipeline = builder.build().unwrap();
+    assert_eq!(pipeline.job.name, "lint");
+    assert_eq!(pipeline.stages, 2);
+}
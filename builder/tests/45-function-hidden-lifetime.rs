@@ -0,0 +1,14 @@
+// `#[builder]` only sees the lifetimes written in a parameter type. A type
+// with a lifetime parameter that is left out entirely, like `Cow<str>`, cannot
+// be told apart from a type without one, so the lifetime has to be written
+// out as `Cow<'_, str>`.
+
+use derive_builder::builder;
+use std::borrow::Cow;
+
+#[builder]
+fn shout(text: Cow<str>) -> String {
+    text.to_uppercase()
+}
+
+fn main() {}
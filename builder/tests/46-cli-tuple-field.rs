@@ -0,0 +1,10 @@
+// Tuple struct fields are only known by position, so under `cli` they need a
+// flag name from `long`, `name` or `each`.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(cli)]
+pub struct Point(#[builder(long = "x")] i32, i32);
+
+fn main() {}
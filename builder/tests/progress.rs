@@ -8,6 +8,43 @@ fn tests() {
     t.pass("tests/05-method-chaining.rs");
     t.pass("tests/06-optional-field.rs");
     t.pass("tests/07-repeated-field.rs");
-    //t.compile_fail("tests/08-unrecognized-attribute.rs");
-    //t.pass("tests/09-redefined-prelude-types.rs");
+    t.compile_fail("tests/08-unrecognized-attribute.rs");
+    t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-typestate.rs");
+    t.compile_fail("tests/11-typestate-missing-field.rs");
+    t.pass("tests/12-error-type.rs");
+    t.pass("tests/13-generics.rs");
+    t.pass("tests/14-tuple-struct.rs");
+    t.pass("tests/15-enum.rs");
+    t.compile_fail("tests/16-unsupported-input.rs");
+    t.pass("tests/17-defaults.rs");
+    t.pass("tests/18-setter-conversions.rs");
+    t.pass("tests/19-validation.rs");
+    t.compile_fail("tests/20-validation-unknown-field.rs");
+    t.pass("tests/21-builder-patterns.rs");
+    t.pass("tests/22-each-collections.rs");
+    t.pass("tests/23-collection-setters.rs");
+    t.pass("tests/24-to-builder.rs");
+    t.pass("tests/25-merge.rs");
+    t.compile_fail("tests/26-merge-invalid-strategy.rs");
+    t.pass("tests/27-env-fallback.rs");
+    t.compile_fail("tests/28-attribute-errors.rs");
+    t.pass("tests/29-sub-builder.rs");
+    t.pass("tests/30-no-std.rs");
+    t.pass("tests/31-naming-and-visibility.rs");
+    t.pass("tests/32-builder-derives.rs");
+    t.pass("tests/33-accessors.rs");
+    t.pass("tests/34-function-builder.rs");
+    t.pass("tests/35-cli.rs");
+    t.pass("tests/36-qualified-types.rs");
+    t.pass("tests/37-explicit-none.rs");
+    t.pass("tests/38-wrap-setter.rs");
+    t.pass("tests/39-const-builder.rs");
+    t.compile_fail("tests/40-const-missing-field.rs");
+    t.pass("tests/41-sub-builder-patterns.rs");
+    t.compile_fail("tests/42-sub-builder-owned-in-mutable.rs");
+    t.compile_fail("tests/43-function-impl-trait.rs");
+    t.pass("tests/44-cli-skip.rs");
+    t.compile_fail("tests/45-function-hidden-lifetime.rs");
+    t.compile_fail("tests/46-cli-tuple-field.rs");
 }
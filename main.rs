@@ -1,4 +1,5 @@
 use derive_builder::Builder;
+use std::collections::HashMap;
 
 #[derive(Builder)]
 pub struct Command {
@@ -6,7 +7,7 @@ pub struct Command {
     #[builder(each = "arg")]
     args: Vec<String>,
     #[builder(each = "env")]
-    env: Vec<String>,
+    env: HashMap<String, String>,
     current_dir: Option<String>,
 }
 
@@ -15,9 +16,12 @@ fn main() {
         .executable("cargo".to_owned())
         .arg("build".to_owned())
         .arg("--release".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned())
         .build()
         .unwrap();
 
     assert_eq!(command.executable, "cargo");
     assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert!(command.current_dir.is_none());
 }